@@ -0,0 +1,56 @@
+//! Sources of the current time.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A source of the current time.
+///
+/// Functions recording or comparing timestamps have a `_with` variant taking
+/// a `&dyn Clock`, so tests can run against a fixed time. A `SystemTime` is a clock
+/// that is stopped at that time.
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+
+    /// The current time as Unix timestamp in seconds, as used by `lastchange` fields.
+    fn timestamp(&self) -> u64 {
+        unix_timestamp(self.now())
+    }
+}
+
+/// The clock of the operating system.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+impl Clock for SystemTime {
+    fn now(&self) -> SystemTime {
+        *self
+    }
+}
+
+/// Convert a point in time to a Unix timestamp in seconds.
+///
+/// Points in time before the Unix epoch are mapped to 0.
+pub(crate) fn unix_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn fixed_clock() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_999);
+        assert_eq!(time.now(), time);
+        assert_eq!(time.timestamp(), 1_700_000_000);
+        assert_eq!((UNIX_EPOCH - Duration::from_secs(1)).timestamp(), 0);
+        assert!(SystemClock.timestamp() > 1_700_000_000);
+    }
+}
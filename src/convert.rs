@@ -0,0 +1,387 @@
+//! Conversion of `Status` documents between SpaceAPI versions.
+//!
+//! The conversion rules mirror the rules `StatusBuilder::build` and
+//! `Status::validate` enforce: fields unknown to the target version are
+//! dropped, renamed fields are moved and fields required by the target
+//! version are filled in where possible.
+
+use crate::sensors::lastchange_field;
+use crate::status::{BuildErrors, Contact, IssueReportChannel, SpecVersion, State, Status};
+
+/// A single modification applied by `Status::to_version`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// The value of `from` was moved to `to`
+    Moved { from: &'static str, to: &'static str },
+    /// The field does not exist in the target version and was dropped
+    Dropped { field: &'static str },
+    /// The field is required by the target version and was filled in
+    Synthesized { field: &'static str },
+    /// The field was rewritten to declare the target version
+    Rewritten { field: &'static str },
+}
+
+impl Change {
+    /// Whether information was lost by this change.
+    pub fn is_lossy(&self) -> bool {
+        matches!(self, Change::Dropped { .. })
+    }
+}
+
+/// The result of converting a `Status` to another version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion {
+    /// The converted document
+    pub status: Status,
+    /// Every change that was applied, in the order it was applied
+    pub changes: Vec<Change>,
+}
+
+impl Conversion {
+    /// Return only the changes that lost information.
+    pub fn lossy_changes(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|change| change.is_lossy())
+    }
+}
+
+/// Drop an optional field, recording the change if it was present.
+fn drop_field<T>(value: &mut Option<T>, field: &'static str, changes: &mut Vec<Change>) {
+    if value.take().is_some() {
+        changes.push(Change::Dropped { field });
+    }
+}
+
+/// Drop a list of sensors, recording the change if it was not empty.
+fn drop_sensors<T>(sensors: &mut Vec<T>, field: &'static str, changes: &mut Vec<Change>) {
+    if !sensors.is_empty() {
+        sensors.clear();
+        changes.push(Change::Dropped { field });
+    }
+}
+
+/// Move an optional field to its new name, unless the new name is already set.
+fn move_field(
+    from_value: &mut Option<String>,
+    to_value: &mut Option<String>,
+    from: &'static str,
+    to: &'static str,
+    changes: &mut Vec<Change>,
+) {
+    match (from_value.take(), to_value.is_some()) {
+        (Some(value), false) => {
+            *to_value = Some(value);
+            changes.push(Change::Moved { from, to });
+        }
+        (Some(_), true) => changes.push(Change::Dropped { field: from }),
+        (None, _) => {}
+    }
+}
+
+/// Derive issue report channels from the contact information present.
+fn issue_report_channels(contact: &Contact) -> Vec<IssueReportChannel> {
+    [
+        (contact.email.is_some(), IssueReportChannel::Email),
+        (contact.issue_mail.is_some(), IssueReportChannel::IssueMail),
+        (contact.twitter.is_some(), IssueReportChannel::Twitter),
+        (contact.ml.is_some(), IssueReportChannel::Ml),
+    ]
+    .into_iter()
+    .filter_map(|(present, channel)| present.then_some(channel))
+    .collect()
+}
+
+impl Status {
+    /// Convert this document to the given SpaceAPI version.
+    ///
+    /// Returns the converted document together with all applied changes.
+    /// If the converted document still violates the rules of the target
+    /// version, e.g. because no issue report channel can be derived for
+    /// v0.13, the remaining violations are returned instead.
+    pub fn to_version(&self, target: SpecVersion) -> Result<Conversion, BuildErrors> {
+        let mut status = self.clone();
+        let mut changes = vec![];
+
+        if target >= SpecVersion::V14 {
+            move_field(
+                &mut status.contact.jabber,
+                &mut status.contact.xmpp,
+                "contact.jabber",
+                "contact.xmpp",
+                &mut changes,
+            );
+            drop_field(&mut status.contact.google, "contact.google", &mut changes);
+            drop_field(&mut status.radio_show, "radio_show", &mut changes);
+            if !status.issue_report_channels.is_empty() {
+                status.issue_report_channels.clear();
+                changes.push(Change::Dropped {
+                    field: "issue_report_channels",
+                });
+            }
+            if let Some(spacefed) = &mut status.spacefed {
+                drop_field(&mut spacefed.spacephone, "spacefed.spacephone", &mut changes);
+            }
+            if status.state.as_ref().is_some_and(|state| state.open.is_none()) {
+                drop_field(&mut status.state, "state", &mut changes);
+            }
+        } else {
+            move_field(
+                &mut status.contact.xmpp,
+                &mut status.contact.jabber,
+                "contact.xmpp",
+                "contact.jabber",
+                &mut changes,
+            );
+            if status.issue_report_channels.is_empty() {
+                status.issue_report_channels = issue_report_channels(&status.contact);
+                if !status.issue_report_channels.is_empty() {
+                    changes.push(Change::Synthesized {
+                        field: "issue_report_channels",
+                    });
+                }
+            }
+            if status.state.is_none() {
+                status.state = Some(State::default());
+                changes.push(Change::Synthesized { field: "state" });
+            }
+            if let Some(spacefed) = &mut status.spacefed {
+                if spacefed.spacephone.is_none() {
+                    spacefed.spacephone = Some(false);
+                    changes.push(Change::Synthesized {
+                        field: "spacefed.spacephone",
+                    });
+                }
+            }
+            drop_field(&mut status.location.timezone, "location.timezone", &mut changes);
+            drop_field(&mut status.links, "links", &mut changes);
+            drop_field(&mut status.membership_plans, "membership_plans", &mut changes);
+            if let Some(sensors) = &mut status.sensors {
+                drop_sensors(&mut sensors.carbondioxide, "sensors.carbondioxide", &mut changes);
+                drop_sensors(
+                    &mut sensors.power_generation,
+                    "sensors.power_generation",
+                    &mut changes,
+                );
+            }
+        }
+
+        if target >= SpecVersion::V15 {
+            drop_field(&mut status.stream, "stream", &mut changes);
+        } else {
+            drop_field(
+                &mut status.location.country_code,
+                "location.country_code",
+                &mut changes,
+            );
+            drop_field(&mut status.location.hint, "location.hint", &mut changes);
+            drop_field(&mut status.location.areas, "location.areas", &mut changes);
+            drop_field(&mut status.contact.signal, "contact.signal", &mut changes);
+            drop_field(&mut status.contact.telegram, "contact.telegram", &mut changes);
+            drop_field(&mut status.linked_spaces, "linked_spaces", &mut changes);
+            if let Some(sensors) = &mut status.sensors {
+                drop_sensors(
+                    &mut sensors.particulate_matter,
+                    "sensors.particulate_matter",
+                    &mut changes,
+                );
+                drop_sensors(
+                    &mut sensors.gas_concentration,
+                    "sensors.gas_concentration",
+                    &mut changes,
+                );
+                changes.extend(
+                    sensors
+                        .remove_lastchange()
+                        .into_iter()
+                        .map(|kind| Change::Dropped {
+                            field: lastchange_field(kind),
+                        }),
+                );
+            }
+        }
+
+        let api = (target == SpecVersion::V0_13).then(|| "0.13".to_owned());
+        if status.api != api {
+            status.api = api;
+            changes.push(Change::Rewritten { field: "api" });
+        }
+        let api_compatibility = target.api_version().map(|version| vec![version]);
+        if status.api_compatibility != api_compatibility {
+            status.api_compatibility = api_compatibility;
+            changes.push(Change::Rewritten {
+                field: "api_compatibility",
+            });
+        }
+
+        let mut errors = vec![];
+        status.verify(&[target], &mut errors);
+        if errors.is_empty() {
+            Ok(Conversion { status, changes })
+        } else {
+            Err(BuildErrors(errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::status::{BuildError, Location, Spacefed};
+    use serde_json::from_str;
+
+    fn v13() -> Status {
+        from_str(
+            r#"{"api":"0.13","space":"a","logo":"b","url":"c","location":{"lat":0.0,"lon":0.0},
+            "contact":{"jabber":"a@b","email":"c@d","google":{"plus":"e"}},
+            "spacefed":{"spacenet":true,"spacesaml":false,"spacephone":false},
+            "issue_report_channels":["email"],"state":{"open":true}}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn convert_v13_to_v14() {
+        let conversion = v13().to_version(SpecVersion::V14).unwrap();
+        let status = conversion.status;
+        assert_eq!(status.contact.xmpp, Some("a@b".into()));
+        assert_eq!(status.contact.jabber, None);
+        assert!(status.issue_report_channels.is_empty());
+        assert_eq!(status.api, None);
+        assert!(status.validate().is_valid());
+        assert_eq!(
+            conversion.changes,
+            vec![
+                Change::Moved {
+                    from: "contact.jabber",
+                    to: "contact.xmpp",
+                },
+                Change::Dropped {
+                    field: "contact.google",
+                },
+                Change::Dropped {
+                    field: "issue_report_channels",
+                },
+                Change::Dropped {
+                    field: "spacefed.spacephone",
+                },
+                Change::Rewritten { field: "api" },
+                Change::Rewritten {
+                    field: "api_compatibility",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn convert_v14_to_v13() {
+        let status: Status = from_str(
+            r#"{"api_compatibility":["14"],"space":"a","logo":"b","url":"c",
+            "location":{"lat":0.0,"lon":0.0,"timezone":"Europe/Zurich"},"contact":{"xmpp":"a@b","ml":"e@f"},
+            "spacefed":{"spacenet":true,"spacesaml":false},"links":[{"name":"a","url":"b"}]}"#,
+        )
+        .unwrap();
+        let conversion = status.to_version(SpecVersion::V0_13).unwrap();
+        assert_eq!(conversion.status.contact.jabber, Some("a@b".into()));
+        assert_eq!(
+            conversion.status.issue_report_channels,
+            vec![IssueReportChannel::Ml]
+        );
+        assert_eq!(conversion.status.state, Some(State::default()));
+        assert_eq!(
+            conversion.status.spacefed,
+            Some(Spacefed {
+                spacenet: true,
+                spacesaml: false,
+                spacephone: Some(false),
+            })
+        );
+        assert_eq!(conversion.status.location, Location::default());
+        assert!(conversion.status.validate().is_valid());
+        assert_eq!(
+            conversion.lossy_changes().collect::<Vec<_>>(),
+            vec![
+                &Change::Dropped {
+                    field: "location.timezone",
+                },
+                &Change::Dropped { field: "links" },
+            ]
+        );
+    }
+
+    #[test]
+    fn convert_v14_to_v13_without_channels() {
+        let status: Status = from_str(
+            r#"{"api_compatibility":["14"],"space":"a","logo":"b","url":"c",
+            "location":{"lat":0.0,"lon":0.0},"contact":{}}"#,
+        )
+        .unwrap();
+        let errors = status.to_version(SpecVersion::V0_13).unwrap_err();
+        assert_eq!(
+            *errors,
+            [BuildError::EmptyList {
+                field: "issue_report_channels",
+                version: SpecVersion::V0_13,
+            }]
+        );
+    }
+
+    #[test]
+    fn convert_v15_to_v14_and_back() {
+        let status: Status = from_str(
+            r#"{"api_compatibility":["15"],"space":"a","logo":"b","url":"c",
+            "location":{"lat":0.0,"lon":0.0,"country_code":"CH"},"contact":{"signal":"+41"},
+            "state":{"open":false},
+            "sensors":{"temperature":[{"location":"Hall","lastchange":5,"unit":"°C","value":20.0}]}}"#,
+        )
+        .unwrap();
+        let v14 = status.to_version(SpecVersion::V14).unwrap();
+        assert_eq!(v14.status.location.country_code, None);
+        assert_eq!(v14.status.contact.signal, None);
+        let sensors = v14.status.sensors.as_ref().unwrap();
+        assert_eq!(sensors.temperature[0].metadata.lastchange, None);
+        assert_eq!(v14.lossy_changes().count(), 3);
+        assert!(v14.changes.contains(&Change::Dropped {
+            field: "sensors.temperature.lastchange",
+        }));
+
+        let v15 = v14.status.to_version(SpecVersion::V15).unwrap();
+        assert_eq!(
+            v15.changes,
+            vec![Change::Rewritten {
+                field: "api_compatibility",
+            }]
+        );
+    }
+
+    #[test]
+    fn convert_v13_to_v14_drops_unknown_state() {
+        let mut status = v13();
+        status.state = Some(State::default());
+        let conversion = status.to_version(SpecVersion::V14).unwrap();
+        assert_eq!(conversion.status.state, None);
+        assert!(conversion.changes.contains(&Change::Dropped { field: "state" }));
+    }
+
+    #[test]
+    fn convert_v14_to_v13_drops_v14_sensors() {
+        let status: Status = from_str(
+            r#"{"api_compatibility":["14"],"space":"a","logo":"b","url":"c",
+            "location":{"lat":0.0,"lon":0.0},"contact":{"email":"c@d"},
+            "sensors":{"carbondioxide":[{"location":"Hall","unit":"ppm","value":600.0}],
+            "power_generation":[{"location":"Roof","unit":"W","value":12.0}]}}"#,
+        )
+        .unwrap();
+        let conversion = status.to_version(SpecVersion::V0_13).unwrap();
+        assert_eq!(conversion.status.sensors, Some(Default::default()));
+        assert_eq!(
+            conversion.lossy_changes().collect::<Vec<_>>(),
+            vec![
+                &Change::Dropped {
+                    field: "sensors.carbondioxide",
+                },
+                &Change::Dropped {
+                    field: "sensors.power_generation",
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Typed access to the custom extensions of a `Status`.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::status::Status;
+
+/// Return the key under which the extension with the given name is stored.
+///
+/// The prefix `ext_` is prepended to the name, if not already present.
+pub(crate) fn extension_key(name: &str) -> String {
+    if name.starts_with("ext_") {
+        name.to_owned()
+    } else {
+        format!("ext_{}", name)
+    }
+}
+
+/// Describes an error occurring when reading or writing a typed extension.
+#[derive(Error, Debug)]
+pub enum ExtensionError {
+    /// The stored value does not match the requested type
+    #[error("extension {key} cannot be decoded")]
+    Decode {
+        key: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// The value cannot be represented as JSON
+    #[error("extension {key} cannot be encoded")]
+    Encode {
+        key: String,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// A typed custom extension.
+///
+/// Implement this trait to define an extension once and read or write it
+/// through `Status::typed_extension` and `Status::set_typed_extension`.
+///
+///     use serde::{Deserialize, Serialize};
+///     use spaceapi::{SpaceApiExtension, Status};
+///
+///     #[derive(Serialize, Deserialize, Debug, PartialEq)]
+///     struct Ccc {
+///         chaostreff: bool,
+///     }
+///
+///     impl SpaceApiExtension for Ccc {
+///         const KEY: &'static str = "ccc";
+///     }
+///
+///     let mut status = Status::default();
+///     status.set_typed_extension(&Ccc { chaostreff: true }).unwrap();
+///     assert!(status.extensions.contains_key("ext_ccc"));
+///     assert_eq!(
+///         status.typed_extension::<Ccc>().unwrap(),
+///         Some(Ccc { chaostreff: true })
+///     );
+pub trait SpaceApiExtension: Serialize + DeserializeOwned {
+    /// The extension name, with or without the `ext_` prefix
+    const KEY: &'static str;
+}
+
+impl Status {
+    /// Decode the extension with the given name.
+    ///
+    /// The prefix `ext_` is prepended to the name, if not already present.
+    /// Returns `Ok(None)` if the extension is not present.
+    pub fn extension<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, ExtensionError> {
+        let key = extension_key(name);
+        match self.extensions.get(&key) {
+            Some(value) => T::deserialize(value)
+                .map(Some)
+                .map_err(|source| ExtensionError::Decode { key, source }),
+            None => Ok(None),
+        }
+    }
+
+    /// Set the extension with the given name, replacing any previous value.
+    ///
+    /// The prefix `ext_` is prepended to the name, if not already present.
+    pub fn set_extension<T: Serialize>(&mut self, name: &str, value: &T) -> Result<(), ExtensionError> {
+        let key = extension_key(name);
+        match serde_json::to_value(value) {
+            Ok(value) => {
+                self.extensions.insert(key, value);
+                Ok(())
+            }
+            Err(source) => Err(ExtensionError::Encode { key, source }),
+        }
+    }
+
+    /// Remove the extension with the given name and return its raw value.
+    ///
+    /// The prefix `ext_` is prepended to the name, if not already present.
+    pub fn remove_extension(&mut self, name: &str) -> Option<Value> {
+        self.extensions.remove(&extension_key(name))
+    }
+
+    /// Decode the typed extension `E`.
+    pub fn typed_extension<E: SpaceApiExtension>(&self) -> Result<Option<E>, ExtensionError> {
+        self.extension(E::KEY)
+    }
+
+    /// Set the typed extension `E`, replacing any previous value.
+    pub fn set_typed_extension<E: SpaceApiExtension>(&mut self, value: &E) -> Result<(), ExtensionError> {
+        self.set_extension(E::KEY, value)
+    }
+
+    /// Remove the typed extension `E` and return its raw value.
+    pub fn remove_typed_extension<E: SpaceApiExtension>(&mut self) -> Option<Value> {
+        self.remove_extension(E::KEY)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Ccc {
+        chaostreff: bool,
+    }
+
+    impl SpaceApiExtension for Ccc {
+        const KEY: &'static str = "ext_ccc";
+    }
+
+    #[test]
+    fn extension_prefix() {
+        let mut status = Status::default();
+        status.set_extension("aaa", &42).unwrap();
+        status.set_extension("ext_bbb", &"x").unwrap();
+        assert_eq!(status.extension::<u32>("ext_aaa").unwrap(), Some(42));
+        assert_eq!(status.extension::<String>("bbb").unwrap(), Some("x".into()));
+        assert_eq!(status.extension::<String>("ccc").unwrap(), None);
+        assert_eq!(status.remove_extension("aaa"), Some(Value::from(42)));
+        assert_eq!(status.extensions.keys().collect::<Vec<_>>(), ["ext_bbb"]);
+    }
+
+    #[test]
+    fn typed_extension_roundtrip() {
+        let mut status = Status::default();
+        status.set_typed_extension(&Ccc { chaostreff: true }).unwrap();
+        let json = serde_json::to_string(&status).unwrap();
+        assert!(json.contains("\"ext_ccc\":{\"chaostreff\":true}"));
+        let status: Status = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            status.typed_extension::<Ccc>().unwrap(),
+            Some(Ccc { chaostreff: true })
+        );
+    }
+
+    #[test]
+    fn typed_extension_decode_error() {
+        let mut status = Status::default();
+        status.set_extension("ccc", &"chaostreff").unwrap();
+        let error = status.typed_extension::<Ccc>().unwrap_err();
+        assert_eq!(error.to_string(), "extension ext_ccc cannot be decoded");
+    }
+
+    #[test]
+    fn encode_error() {
+        let mut status = Status::default();
+        let value: BTreeMap<(u8, u8), u8> = [((1, 2), 3)].into_iter().collect();
+        let error = status.set_extension("map", &value).unwrap_err();
+        assert_eq!(error.to_string(), "extension ext_map cannot be encoded");
+        assert!(status.extensions.is_empty());
+    }
+}
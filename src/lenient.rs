@@ -0,0 +1,291 @@
+//! Forgiving deserialization of `Status` documents.
+//!
+//! Many published documents contain small type errors, e.g. `"open": "true"`
+//! or coordinates given as strings. The functions in this module fix these
+//! common mistakes before deserializing the document, and report every fix.
+//! Afterwards, the document is parsed like `parse::from_value` does in
+//! lenient mode.
+
+use std::fmt;
+
+use serde::Deserialize;
+use serde_json::{Number, Value};
+
+use crate::parse::{self, Mode, ParseError, Warning};
+use crate::status::{IssueReportChannel, Status};
+
+/// A modification applied to a document to make it deserializable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fixup {
+    /// The value at `path` had the wrong type and was converted
+    Converted { path: String, from: Value, to: Value },
+    /// The value at `path` is not allowed and was removed
+    Removed { path: String, value: Value },
+}
+
+impl fmt::Display for Fixup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fixup::Converted { path, from, to } => write!(f, "converted {} from {} to {}", path, from, to),
+            Fixup::Removed { path, value } => write!(f, "removed {} at {}", value, path),
+        }
+    }
+}
+
+/// A `Status` parsed by `lenient::from_str` or `lenient::from_value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixed {
+    pub status: Status,
+    /// The fixes applied before deserializing, in the order they were applied
+    pub fixups: Vec<Fixup>,
+    /// The problems tolerated by `parse::Mode::Lenient`
+    pub warnings: Vec<Warning>,
+}
+
+/// Convert the value at `path`, if present and `convert` returns a new value.
+fn convert(document: &mut Value, path: &str, fixups: &mut Vec<Fixup>, convert: fn(&Value) -> Option<Value>) {
+    if let Some(value) = document.pointer_mut(path) {
+        if let Some(converted) = convert(value) {
+            fixups.push(Fixup::Converted {
+                path: path.to_owned(),
+                from: std::mem::replace(value, converted.clone()),
+                to: converted,
+            });
+        }
+    }
+}
+
+/// Convert strings and the numbers 0 and 1 to booleans.
+fn to_bool(value: &Value) -> Option<Value> {
+    let open = match value {
+        Value::String(string) => match string.trim().to_ascii_lowercase().as_str() {
+            "true" | "open" | "1" => true,
+            "false" | "closed" | "0" => false,
+            _ => return None,
+        },
+        Value::Number(number) if number.as_u64() == Some(1) => true,
+        Value::Number(number) if number.as_u64() == Some(0) => false,
+        _ => return None,
+    };
+    Some(Value::Bool(open))
+}
+
+/// Convert strings containing a number to numbers.
+fn to_float(value: &Value) -> Option<Value> {
+    let number = value.as_str()?.trim().parse().ok()?;
+    Number::from_f64(number).map(Value::Number)
+}
+
+/// Convert non-negative floats and strings containing a timestamp to integers.
+fn to_timestamp(value: &Value) -> Option<Value> {
+    let timestamp = match value {
+        Value::Number(number) if number.is_f64() => number.as_f64()?,
+        Value::String(string) => string.trim().parse().ok()?,
+        _ => return None,
+    };
+    if timestamp.is_finite() && timestamp >= 0.0 && timestamp <= u64::MAX as f64 {
+        Some(Value::from(timestamp as u64))
+    } else {
+        None
+    }
+}
+
+/// Return the JSON pointers of all sensor lists in a document.
+fn sensor_lists(document: &Value) -> Vec<String> {
+    let Some(sensors) = document.get("sensors").and_then(Value::as_object) else {
+        return vec![];
+    };
+    let mut lists = vec![];
+    for (kind, value) in sensors {
+        match value {
+            Value::Array(_) => lists.push(format!("/sensors/{}", parse::escape(kind))),
+            Value::Object(sublists) if kind == "radiation" => lists.extend(
+                sublists
+                    .keys()
+                    .map(|radiation_kind| format!("/sensors/radiation/{}", parse::escape(radiation_kind))),
+            ),
+            _ => {}
+        }
+    }
+    lists
+}
+
+/// Number of entries of the list at `path`.
+fn list_len(document: &Value, path: &str) -> usize {
+    document
+        .pointer(path)
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len)
+}
+
+/// Remove all entries of `issue_report_channels` that are not known channels.
+fn remove_unknown_channels(document: &mut Value, fixups: &mut Vec<Fixup>) {
+    let Some(channels) = document
+        .get_mut("issue_report_channels")
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+    let mut index = 0;
+    channels.retain(|channel| {
+        let known = IssueReportChannel::deserialize(channel).is_ok();
+        if !known {
+            fixups.push(Fixup::Removed {
+                path: format!("/issue_report_channels/{}", index),
+                value: channel.clone(),
+            });
+        }
+        index += 1;
+        known
+    });
+}
+
+/// Fix common mistakes in a raw document.
+fn fix(document: &mut Value) -> Vec<Fixup> {
+    let mut fixups = vec![];
+    convert(document, "/location/lat", &mut fixups, to_float);
+    convert(document, "/location/lon", &mut fixups, to_float);
+    remove_unknown_channels(document, &mut fixups);
+    convert(document, "/state/open", &mut fixups, to_bool);
+    convert(document, "/state/lastchange", &mut fixups, to_timestamp);
+    for index in 0..list_len(document, "/events") {
+        let path = format!("/events/{}/timestamp", index);
+        convert(document, &path, &mut fixups, to_timestamp);
+    }
+    for list in sensor_lists(document) {
+        for index in 0..list_len(document, &list) {
+            let path = format!("{}/{}/lastchange", list, index);
+            convert(document, &path, &mut fixups, to_timestamp);
+        }
+    }
+    fixups
+}
+
+/// Parse a `Status` from a JSON string, fixing common mistakes.
+pub fn from_str(json: &str) -> Result<Fixed, ParseError> {
+    from_value(parse::to_value(json)?).map_err(|error| parse::locate(error, json))
+}
+
+/// Parse a `Status` from a JSON value, fixing common mistakes.
+pub fn from_value(mut value: Value) -> Result<Fixed, ParseError> {
+    let fixups = fix(&mut value);
+    let parsed = parse::from_value(value, Mode::Lenient)?;
+    Ok(Fixed {
+        status: parsed.status,
+        fixups,
+        warnings: parsed.warnings,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn document() -> Value {
+        json!({
+            "api": "0.13",
+            "space": "a",
+            "logo": "b",
+            "url": "c",
+            "location": {"lat": "47.2", "lon": 8.5},
+            "contact": {"email": "c@d"},
+            "issue_report_channels": ["email", "irc"],
+            "state": {"open": "true", "lastchange": 1700000000.5},
+            "events": [{"name": "x", "type": "check-in", "timestamp": "1700000001"}],
+            "sensors": {
+                "temperature": [{"location": "Hall", "unit": "°C", "value": 20.0, "lastchange": 1.7e9}],
+                "radiation": {"beta": [{"unit": "cpm", "value": 3.0, "lastchange": 1700000002.0}]}
+            }
+        })
+    }
+
+    #[test]
+    fn strict_rejects_mistakes() {
+        assert!(serde_json::from_value::<Status>(document()).is_err());
+    }
+
+    #[test]
+    fn fix_mistakes() {
+        let fixed = from_value(document()).unwrap();
+        let status = fixed.status;
+        assert_eq!(status.location.lat, 47.2);
+        assert_eq!(status.issue_report_channels, [IssueReportChannel::Email]);
+        let state = status.state.unwrap();
+        assert_eq!(state.open, Some(true));
+        assert_eq!(state.lastchange, Some(1_700_000_000));
+        assert_eq!(status.events.unwrap()[0].timestamp, 1_700_000_001);
+        let sensors = status.sensors.unwrap();
+        assert_eq!(sensors.temperature[0].metadata.lastchange, Some(1_700_000_000));
+        assert_eq!(
+            sensors.radiation.unwrap().beta.unwrap()[0].metadata.lastchange,
+            Some(1_700_000_002)
+        );
+
+        let paths: Vec<_> = fixed
+            .fixups
+            .iter()
+            .map(|fixup| match fixup {
+                Fixup::Converted { path, .. } | Fixup::Removed { path, .. } => path.as_str(),
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/location/lat",
+                "/issue_report_channels/1",
+                "/state/open",
+                "/state/lastchange",
+                "/events/0/timestamp",
+                "/sensors/radiation/beta/0/lastchange",
+                "/sensors/temperature/0/lastchange",
+            ]
+        );
+        assert_eq!(
+            fixed.fixups[0].to_string(),
+            "converted /location/lat from \"47.2\" to 47.2"
+        );
+        assert_eq!(
+            fixed.fixups[1].to_string(),
+            "removed \"irc\" at /issue_report_channels/1"
+        );
+        assert!(fixed.warnings.is_empty());
+    }
+
+    #[test]
+    fn fix_sensor_kinds_needing_escapes() {
+        let mut document = document();
+        document["sensors"]["noise/level~db"] = json!([{"value": 40, "lastchange": 1.7e9}]);
+        let fixed = from_value(document).unwrap();
+        assert!(fixed.fixups.iter().any(|fixup| matches!(
+            fixup,
+            Fixup::Converted { path, .. } if path == "/sensors/noise~1level~0db/0/lastchange"
+        )));
+        let sensors = fixed.status.sensors.unwrap();
+        assert_eq!(
+            sensors.other["noise/level~db"][0]["lastchange"],
+            json!(1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn keep_unfixable_mistakes() {
+        let mut document = document();
+        document["state"]["open"] = json!("maybe");
+        let error = from_value(document.clone()).unwrap_err();
+        assert!(matches!(error, ParseError::Json(_)));
+
+        let json = serde_json::to_string_pretty(&document).unwrap();
+        let error = match from_str(&json).unwrap_err() {
+            ParseError::Json(error) => error,
+            error => panic!("unexpected error {:?}", error),
+        };
+        assert_eq!(error.pointer, "/state/open");
+        let line = json.lines().position(|line| line.contains("\"maybe\"")).unwrap() + 1;
+        assert_eq!((error.line, error.column), (line, 13));
+        assert_eq!(
+            error.snippet.as_deref(),
+            Some("    \"open\": \"maybe\"\n            ^")
+        );
+    }
+}
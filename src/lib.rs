@@ -7,8 +7,9 @@
 //! [`Deserialize`](https://docs.serde.rs/serde/de/trait.Deserialize.html)
 //! traits for all structs.
 //!
-//! The currently supported SpaceAPI version is 0.13. It is not yet fully
-//! implemented.
+//! The currently supported SpaceAPI versions are 0.13, v14 and v15. Use
+//! `StatusBuilder::v0_13`, `StatusBuilder::v14`, `StatusBuilder::v15` or
+//! `StatusBuilder::mixed` to target a specific version.
 //!
 //! If you want to implement a SpaceAPI server on top of these types, you
 //! might want to take a look at the [`spaceapi_server`
@@ -72,12 +73,23 @@
 //!     println!("{:?}", decoded);
 //!
 //!     // Output:
-//!     // Location { address: None, lat: 47.22936000000001, lon: 8.829490000000002, timezone: None }
+//!     // Location { address: None, lat: 47.22936, lon: 8.82949, timezone: None, country_code: None, hint: None, areas: None }
 //!     # }
 
+mod clock;
+pub mod convert;
+mod extensions;
+pub mod lenient;
+pub mod parse;
+#[cfg(feature = "schema-validation")]
+pub mod schema;
 pub mod sensors;
 mod status;
+mod validation;
+pub use crate::clock::*;
+pub use crate::extensions::*;
 pub use crate::status::*;
+pub use crate::validation::*;
 
 /// Return own crate version. Used in API responses.
 pub fn get_version() -> &'static str {
@@ -0,0 +1,782 @@
+//! Checked deserialization of `Status` documents.
+//!
+//! Deserializing a `Status` directly through Serde silently collects every
+//! unknown top-level key in `Status::extensions`. The functions in this module
+//! additionally check that all of those keys carry the `ext_` prefix.
+//!
+//! In lenient mode, sensors that cannot be decoded are skipped instead of
+//! failing the whole document.
+//!
+//! Documents that cannot be deserialized are reported as `JsonError`, which
+//! tells the JSON pointer of the offending value and, when parsing a string,
+//! the line, column and an excerpt of the input.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::error::Category;
+use serde_json::{Map, Value};
+use serde_path_to_error::Segment;
+use thiserror::Error;
+
+use crate::sensors::{
+    AccountBalanceSensor, BarometerSensor, BeverageSupplySensor, CarbonDioxideSensor, DoorLockedSensor,
+    GasConcentrationSensor, HumiditySensor, NetworkConnectionsSensor, NetworkTrafficSensor,
+    ParticulateMatterSensor, PeopleNowPresentSensor, PowerConsumptionSensor, PowerGenerationSensor,
+    RadiationSensor, RadiationSensorKind, TemperatureSensor, TotalMemberCountSensor, WindSensor,
+};
+use crate::status::Status;
+
+/// How top-level keys that are neither SpaceAPI fields nor `ext_` extensions are treated.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Reject documents containing such keys
+    #[default]
+    Strict,
+    /// Accept documents containing such keys and report a warning for each of
+    /// them. Sensors that cannot be decoded are skipped with a warning.
+    Lenient,
+}
+
+/// A problem that was tolerated when parsing in lenient mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// A top-level key is neither a SpaceAPI field nor prefixed with `ext_`
+    UnknownKey { key: String },
+    /// A sensor or list of sensors cannot be decoded and was skipped
+    InvalidSensor {
+        /// JSON pointer to the skipped value, e.g. `/sensors/temperature/0`
+        path: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::UnknownKey { key } => write!(f, "unknown key {:?} without ext_ prefix", key),
+            Warning::InvalidSensor { path, reason } => write!(f, "skipped sensor at {}: {}", path, reason),
+        }
+    }
+}
+
+/// Describes an error occurring when parsing a `Status`.
+#[derive(Error, Debug)]
+pub enum ParseError {
+    /// The input is not a valid SpaceAPI document
+    #[error(transparent)]
+    Json(#[from] JsonError),
+
+    /// The document contains top-level keys without `ext_` prefix
+    #[error("unknown keys without ext_ prefix: {}", .0.join(", "))]
+    UnknownKeys(Vec<String>),
+}
+
+/// Describes why a document cannot be deserialized, and where.
+#[derive(Error, Debug)]
+#[error("invalid document{}: {source}", if .pointer.is_empty() { String::new() } else { format!(" at {}", .pointer) })]
+pub struct JsonError {
+    /// JSON pointer to the offending value, e.g. `/sensors/temperature/3/location`.
+    /// For a missing field, this points to where the field is expected. Empty
+    /// if the document as a whole is invalid.
+    pub pointer: String,
+    /// What was expected at `pointer`, e.g. `f64` or ``field `location` ``
+    pub expected: Option<String>,
+    /// Line of the error in the input, starting at 1. 0 if unknown.
+    pub line: usize,
+    /// Column of the error in the input in bytes, starting at 1. 0 if unknown.
+    pub column: usize,
+    /// The input around the error, followed by a line marking the column
+    pub snippet: Option<String>,
+    source: serde_json::Error,
+}
+
+/// Characters of context shown on either side of an error in a snippet.
+const SNIPPET_CONTEXT: usize = 30;
+
+/// The metadata fields every sensor may have, with what they must be.
+const METADATA_FIELDS: [(&str, &str); 4] = [
+    ("name", "a string"),
+    ("location", "a string"),
+    ("description", "a string"),
+    ("lastchange", "u64"),
+];
+
+impl JsonError {
+    /// Describe `error`, which occurred at `pointer` in `document`.
+    fn new(error: serde_json::Error, pointer: String, input: Option<&str>, document: Option<&Value>) -> Self {
+        let metadata_field = document.and_then(|document| invalid_metadata_field(document, &pointer));
+        let (pointer, expected) = match metadata_field {
+            Some((field, expected)) => (format!("{}/{}", pointer, field), Some(expected.to_owned())),
+            None => from_message(&error, pointer),
+        };
+        let snippet = input.and_then(|input| snippet(input, error.line(), error.column()));
+        JsonError {
+            pointer,
+            expected,
+            line: error.line(),
+            column: error.column(),
+            snippet,
+            source: error,
+        }
+    }
+}
+
+/// Find the metadata field of the sensor at `pointer` that has the wrong type.
+///
+/// Sensors deserialize their metadata through `#[serde(flatten)]`, which hides
+/// the metadata fields from the recorded path, so errors in them point at the
+/// sensor itself. Such sensors are checked field by field instead.
+fn invalid_metadata_field(document: &Value, pointer: &str) -> Option<(&'static str, &'static str)> {
+    if !pointer.starts_with("/sensors/") {
+        return None;
+    }
+    let sensor = document.pointer(pointer)?.as_object()?;
+    METADATA_FIELDS
+        .into_iter()
+        .find(|(field, _)| match sensor.get(*field) {
+            None | Some(Value::Null) => false,
+            Some(value) if *field == "lastchange" => value.as_u64().is_none(),
+            Some(value) => !value.is_string(),
+        })
+}
+
+/// Complete `pointer` and find what was expected from the message of a data error.
+///
+/// serde_json offers no structured access to either, so this relies on the
+/// wording of its missing field, invalid type and invalid value messages.
+/// Other errors keep `pointer` and have no expectation.
+fn from_message(error: &serde_json::Error, pointer: String) -> (String, Option<String>) {
+    if error.classify() != Category::Data {
+        return (pointer, None);
+    }
+    let message = error.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(end) if error.line() != 0 => &message[..end],
+        _ => &message,
+    };
+    match message.strip_prefix("missing field `") {
+        Some(field) => {
+            let field = field.trim_end_matches('`');
+            (
+                format!("{}/{}", pointer, escape(field)),
+                Some(format!("field `{}`", field)),
+            )
+        }
+        None => {
+            let expected = message
+                .split_once(", expected ")
+                .map(|(_, expected)| expected.to_owned());
+            (pointer, expected)
+        }
+    }
+}
+
+/// Add the position of `error` in `json` if it was not known yet.
+///
+/// Errors found in a `Value` parsed from `json`, e.g. in lenient mode, carry
+/// no line and column. If deserializing `json` directly fails at the same
+/// pointer, that error is used. Otherwise the value at the pointer, or at
+/// its closest existing parent, is looked up in `json`.
+pub(crate) fn locate(error: ParseError, json: &str) -> ParseError {
+    let ParseError::Json(mut error) = error else {
+        return error;
+    };
+    if error.line != 0 {
+        return ParseError::Json(error);
+    }
+    if let Err(ParseError::Json(direct)) = deserialize_str::<Status>(json) {
+        if direct.pointer == error.pointer {
+            return ParseError::Json(direct);
+        }
+    }
+    let mut pointer = error.pointer.as_str();
+    let offset = loop {
+        if let Some(offset) = offset_of(json, pointer) {
+            break offset;
+        }
+        match pointer.rsplit_once('/') {
+            Some((parent, _)) => pointer = parent,
+            None => return ParseError::Json(error),
+        }
+    };
+    let line_start = json[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    error.line = json[..offset].matches('\n').count() + 1;
+    error.column = offset - line_start + 1;
+    error.snippet = snippet(json, error.line, error.column);
+    ParseError::Json(error)
+}
+
+/// Find the byte offset of the value at `pointer` in the JSON text `json`.
+fn offset_of(json: &str, pointer: &str) -> Option<usize> {
+    let mut scanner = Scanner { json, pos: 0 };
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        scanner.skip_whitespace();
+        match scanner.peek()? {
+            b'{' => {
+                scanner.pos += 1;
+                loop {
+                    scanner.skip_whitespace();
+                    let key = scanner.string()?;
+                    scanner.skip_whitespace();
+                    scanner.expect(b':')?;
+                    if key == segment {
+                        break;
+                    }
+                    scanner.skip_value()?;
+                    scanner.skip_whitespace();
+                    scanner.expect(b',')?;
+                }
+            }
+            b'[' => {
+                scanner.pos += 1;
+                for _ in 0..segment.parse::<usize>().ok()? {
+                    scanner.skip_value()?;
+                    scanner.skip_whitespace();
+                    scanner.expect(b',')?;
+                }
+                scanner.skip_whitespace();
+                if scanner.peek()? == b']' {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+    }
+    scanner.skip_whitespace();
+    Some(scanner.pos)
+}
+
+/// A minimal cursor over JSON text, which only tells values apart.
+struct Scanner<'a> {
+    json: &'a str,
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.json.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.peek()? == byte).then(|| self.pos += 1)
+    }
+
+    /// Read a string, returning it unescaped.
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.skip_string()?;
+        serde_json::from_str(&self.json[start..self.pos]).ok()
+    }
+
+    fn skip_string(&mut self) -> Option<()> {
+        self.expect(b'"')?;
+        loop {
+            match self.peek()? {
+                b'"' => break,
+                b'\\' => self.pos += 2,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        Some(())
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_whitespace();
+        let mut depth = 0usize;
+        loop {
+            match self.peek()? {
+                b'"' => self.skip_string()?,
+                b'{' | b'[' => {
+                    depth += 1;
+                    self.pos += 1;
+                }
+                b'}' | b']' => {
+                    depth = depth.checked_sub(1)?;
+                    self.pos += 1;
+                }
+                _ => self.pos += 1,
+            }
+            let at_end = match self.peek() {
+                None | Some(b',' | b'}' | b']') => true,
+                Some(byte) => byte.is_ascii_whitespace(),
+            };
+            if depth == 0 && at_end {
+                return Some(());
+            }
+        }
+    }
+}
+
+/// Escape a key for use as JSON pointer segment.
+pub(crate) fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Convert a path recorded during deserialization to a JSON pointer.
+///
+/// Segments that could not be tracked, e.g. inside untagged enums, are left out.
+fn pointer(path: &serde_path_to_error::Path) -> String {
+    let mut pointer = String::new();
+    for segment in path {
+        match segment {
+            Segment::Seq { index } => pointer.push_str(&format!("/{}", index)),
+            Segment::Map { key } => pointer.push_str(&format!("/{}", escape(key))),
+            Segment::Enum { variant } => pointer.push_str(&format!("/{}", escape(variant))),
+            Segment::Unknown => {}
+        }
+    }
+    pointer
+}
+
+/// Cut the input around `column` of `line`, and mark the column below.
+fn snippet(input: &str, line: usize, column: usize) -> Option<String> {
+    let text = input.lines().nth(line.checked_sub(1)?)?;
+    let offset = column.saturating_sub(1).min(text.len());
+    let mut start = offset.saturating_sub(SNIPPET_CONTEXT);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (offset + SNIPPET_CONTEXT).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+    let marker = text[start..offset.max(start)].chars().count();
+    Some(format!("{}\n{}^", &text[start..end], " ".repeat(marker)))
+}
+
+/// Deserialize a `T`, returning the error together with the pointer to where it occurred.
+fn deserialize<'de, T, D>(deserializer: D) -> Result<T, (serde_json::Error, String)>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de, Error = serde_json::Error>,
+{
+    serde_path_to_error::deserialize(deserializer).map_err(|error| {
+        let pointer = pointer(error.path());
+        (error.into_inner(), pointer)
+    })
+}
+
+/// Deserialize a `T` from a JSON string, recording where the error occurred.
+fn deserialize_str<T: DeserializeOwned>(json: &str) -> Result<T, ParseError> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let value = deserialize(&mut deserializer).map_err(|(error, pointer)| {
+        let document = serde_json::from_str(json).ok();
+        JsonError::new(error, pointer, Some(json), document.as_ref())
+    })?;
+    deserializer
+        .end()
+        .map_err(|error| JsonError::new(error, String::new(), Some(json), None))?;
+    Ok(value)
+}
+
+/// Parse a JSON string into a raw value.
+pub(crate) fn to_value(json: &str) -> Result<Value, ParseError> {
+    deserialize_str(json)
+}
+
+/// A successfully parsed `Status` together with all tolerated problems.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parsed {
+    pub status: Status,
+    /// Always empty in strict mode
+    pub warnings: Vec<Warning>,
+}
+
+fn check(status: Status, mode: Mode, mut warnings: Vec<Warning>) -> Result<Parsed, ParseError> {
+    let unknown_keys: Vec<String> = status.unknown_keys().map(str::to_owned).collect();
+    match mode {
+        Mode::Strict if !unknown_keys.is_empty() => Err(ParseError::UnknownKeys(unknown_keys)),
+        Mode::Strict => Ok(Parsed { status, warnings }),
+        Mode::Lenient => {
+            warnings.extend(unknown_keys.into_iter().map(|key| Warning::UnknownKey { key }));
+            Ok(Parsed { status, warnings })
+        }
+    }
+}
+
+/// Check whether a value decodes as `T`.
+fn decode<T: DeserializeOwned>(value: &Value) -> Result<(), serde_json::Error> {
+    T::deserialize(value).map(drop)
+}
+
+type Decoder = fn(&Value) -> Result<(), serde_json::Error>;
+
+/// Remove every entry of the list at `path` in `parent` that does not decode,
+/// or the whole list if it is not an array.
+fn remove_invalid_entries(
+    parent: &mut Map<String, Value>,
+    key: &str,
+    path: &str,
+    decoder: Decoder,
+    warnings: &mut Vec<Warning>,
+) {
+    let Some(list) = parent.get_mut(key) else {
+        return;
+    };
+    let Some(entries) = list.as_array_mut() else {
+        warnings.push(Warning::InvalidSensor {
+            path: path.to_owned(),
+            reason: "expected a list of sensors".to_owned(),
+        });
+        parent.remove(key);
+        return;
+    };
+    let mut index = 0;
+    entries.retain(|entry| {
+        let keep = match decoder(entry) {
+            Ok(()) => true,
+            Err(error) => {
+                warnings.push(Warning::InvalidSensor {
+                    path: format!("{}/{}", path, index),
+                    reason: error.to_string(),
+                });
+                false
+            }
+        };
+        index += 1;
+        keep
+    });
+}
+
+/// Remove all sensors of known kinds that do not decode from a raw document.
+fn remove_invalid_sensors(document: &mut Value, warnings: &mut Vec<Warning>) {
+    let Some(sensors) = document.get_mut("sensors").and_then(Value::as_object_mut) else {
+        return;
+    };
+    let decoders: [(&str, Decoder); 16] = [
+        ("temperature", decode::<TemperatureSensor>),
+        ("door_locked", decode::<DoorLockedSensor>),
+        ("barometer", decode::<BarometerSensor>),
+        ("humidity", decode::<HumiditySensor>),
+        ("beverage_supply", decode::<BeverageSupplySensor>),
+        ("power_consumption", decode::<PowerConsumptionSensor>),
+        ("wind", decode::<WindSensor>),
+        ("network_connections", decode::<NetworkConnectionsSensor>),
+        ("account_balance", decode::<AccountBalanceSensor>),
+        ("total_member_count", decode::<TotalMemberCountSensor>),
+        ("people_now_present", decode::<PeopleNowPresentSensor>),
+        ("network_traffic", decode::<NetworkTrafficSensor>),
+        ("carbondioxide", decode::<CarbonDioxideSensor>),
+        ("power_generation", decode::<PowerGenerationSensor>),
+        ("particulate_matter", decode::<ParticulateMatterSensor>),
+        ("gas_concentration", decode::<GasConcentrationSensor>),
+    ];
+    for (key, decoder) in decoders {
+        let path = format!("/sensors/{}", key);
+        remove_invalid_entries(sensors, key, &path, decoder, warnings);
+    }
+
+    match sensors.get_mut("radiation") {
+        Some(Value::Object(radiation)) => {
+            for kind in [
+                RadiationSensorKind::Alpha,
+                RadiationSensorKind::Beta,
+                RadiationSensorKind::Gamma,
+                RadiationSensorKind::BetaGamma,
+            ] {
+                let path = format!("/sensors/radiation/{}", kind.as_str());
+                remove_invalid_entries(
+                    radiation,
+                    kind.as_str(),
+                    &path,
+                    decode::<RadiationSensor>,
+                    warnings,
+                );
+            }
+        }
+        Some(_) => {
+            warnings.push(Warning::InvalidSensor {
+                path: "/sensors/radiation".to_owned(),
+                reason: "expected an object of radiation sensor lists".to_owned(),
+            });
+            sensors.remove("radiation");
+        }
+        None => {}
+    }
+}
+
+/// Parse a `Status` from a JSON string.
+pub fn from_str(json: &str, mode: Mode) -> Result<Parsed, ParseError> {
+    match mode {
+        Mode::Strict => check(deserialize_str(json)?, mode, vec![]),
+        Mode::Lenient => from_value(to_value(json)?, mode).map_err(|error| locate(error, json)),
+    }
+}
+
+/// Parse a `Status` from a JSON value.
+pub fn from_value(mut value: Value, mode: Mode) -> Result<Parsed, ParseError> {
+    let mut warnings = vec![];
+    if mode == Mode::Lenient {
+        remove_invalid_sensors(&mut value, &mut warnings);
+    }
+    let status =
+        deserialize(&value).map_err(|(error, pointer)| JsonError::new(error, pointer, None, Some(&value)))?;
+    check(status, mode, warnings)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DOCUMENT: &str = r#"{"api_compatibility":["14"],"space":"a","logo":"b","url":"c",
+        "location":{"lat":0.0,"lon":0.0},"contact":{},"ext_ccc":"chaostreff","sensor":{},"contacts":{}}"#;
+
+    #[test]
+    fn strict_rejects_unknown_keys() {
+        let error = from_str(DOCUMENT, Mode::Strict).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown keys without ext_ prefix: contacts, sensor"
+        );
+    }
+
+    #[test]
+    fn strict_accepts_extensions() {
+        let document = DOCUMENT.replace(r#","sensor":{},"contacts":{}"#, "");
+        let parsed = from_str(&document, Mode::Strict).unwrap();
+        assert!(parsed.warnings.is_empty());
+        assert_eq!(parsed.status.extensions.len(), 1);
+    }
+
+    #[test]
+    fn lenient_collects_warnings() {
+        let parsed = from_str(DOCUMENT, Mode::Lenient).unwrap();
+        assert_eq!(
+            parsed.warnings,
+            vec![
+                Warning::UnknownKey {
+                    key: "contacts".into()
+                },
+                Warning::UnknownKey { key: "sensor".into() },
+            ]
+        );
+        assert_eq!(parsed.status.extensions.len(), 3);
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert!(matches!(
+            from_value(Value::Null, Mode::Lenient),
+            Err(ParseError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn lenient_skips_invalid_sensors() {
+        let document = DOCUMENT.replace(
+            r#""sensor":{}"#,
+            r#""sensors":{"temperature":[{"unit":"°C","value":20.0},{"location":"Hall","unit":"°C","value":21.0}],
+            "door_locked":{"location":"Front"},"radiation":{"beta":[{"unit":"cpm"}]},
+            "noise":[{"value":"loud"}]}"#,
+        );
+        assert!(from_str(&document, Mode::Strict).is_err());
+
+        let parsed = from_str(&document, Mode::Lenient).unwrap();
+        let sensors = parsed.status.sensors.unwrap();
+        assert_eq!(sensors.temperature.len(), 1);
+        assert_eq!(sensors.temperature[0].metadata.location, "Hall");
+        assert!(sensors.door_locked.is_empty());
+        assert_eq!(sensors.radiation.unwrap().beta, Some(vec![]));
+        assert!(sensors.other.contains_key("noise"));
+
+        let paths: Vec<_> = parsed
+            .warnings
+            .iter()
+            .map(|warning| match warning {
+                Warning::InvalidSensor { path, .. } => path.as_str(),
+                Warning::UnknownKey { key } => key.as_str(),
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/sensors/temperature/0",
+                "/sensors/door_locked",
+                "/sensors/radiation/beta/0",
+                "contacts"
+            ]
+        );
+        assert_eq!(
+            parsed.warnings[0].to_string(),
+            "skipped sensor at /sensors/temperature/0: missing field `location`"
+        );
+    }
+
+    fn json_error(document: &str) -> JsonError {
+        match from_str(document, Mode::Strict).unwrap_err() {
+            ParseError::Json(error) => error,
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn error_pointer_to_missing_field() {
+        let document = DOCUMENT.replace(
+            r#""sensor":{},"contacts":{}"#,
+            "\n\"sensors\":{\"temperature\":[{\"location\":\"Hall\",\"unit\":\"°C\",\"value\":21.0},{\"unit\":\"°C\",\"value\":20.0}]}",
+        );
+        let error = json_error(&document);
+        assert_eq!(error.pointer, "/sensors/temperature/1/location");
+        assert_eq!(error.expected.as_deref(), Some("field `location`"));
+        assert_eq!((error.line, error.column), (3, 99));
+        assert_eq!(
+            error.to_string(),
+            "invalid document at /sensors/temperature/1/location: missing field `location` at line 3 column 99"
+        );
+    }
+
+    #[test]
+    fn error_pointer_to_invalid_value() {
+        let document = DOCUMENT.replace(r#""lat":0.0"#, r#""lat":"north""#);
+        let error = json_error(&document);
+        assert_eq!(error.pointer, "/location/lat");
+        assert_eq!(error.expected.as_deref(), Some("f64"));
+        assert_eq!(
+            error.snippet.as_deref(),
+            Some("      \"location\":{\"lat\":\"north\",\"lon\":0.0},\"contact\":{},\"ext\n                              ^")
+        );
+
+        let value: Value = serde_json::from_str(&document).unwrap();
+        let error = match from_value(value, Mode::Strict).unwrap_err() {
+            ParseError::Json(error) => error,
+            error => panic!("unexpected error {:?}", error),
+        };
+        assert_eq!(error.pointer, "/location/lat");
+        assert_eq!((error.line, error.column, error.snippet), (0, 0, None));
+    }
+
+    #[test]
+    fn error_pointer_to_invalid_metadata() {
+        let sensors = r#""sensors":{"temperature":[{"location":"Hall","unit":"°C","value":21.0},
+            {"location":"Lab","unit":"°C","value":20.0,"name":5}]}"#;
+        let document = DOCUMENT.replace(r#""sensor":{},"contacts":{}"#, sensors);
+        let error = json_error(&document);
+        assert_eq!(error.pointer, "/sensors/temperature/1/name");
+        assert_eq!(error.expected.as_deref(), Some("a string"));
+
+        let document = document.replace(
+            r#""location":"Lab","unit":"°C","value":20.0,"name":5"#,
+            r#""location":["Lab"],"unit":"°C","value":20.0"#,
+        );
+        let error = json_error(&document);
+        assert_eq!(error.pointer, "/sensors/temperature/1/location");
+        assert_eq!(error.expected.as_deref(), Some("a string"));
+
+        let sensors = r#""sensors":{"radiation":{"beta":[{"unit":"cpm","value":1.0,"lastchange":-1}]}}"#;
+        let document = DOCUMENT.replace(r#""sensor":{},"contacts":{}"#, sensors);
+        let value: Value = serde_json::from_str(&document).unwrap();
+        let error = match from_value(value, Mode::Strict).unwrap_err() {
+            ParseError::Json(error) => error,
+            error => panic!("unexpected error {:?}", error),
+        };
+        assert_eq!(error.pointer, "/sensors/radiation/beta/0/lastchange");
+        assert_eq!(error.expected.as_deref(), Some("u64"));
+    }
+
+    #[test]
+    fn error_position_in_lenient_mode() {
+        let document = DOCUMENT.replace(r#""lat":0.0"#, r#""lat":"north""#);
+        let error = match from_str(&document, Mode::Lenient).unwrap_err() {
+            ParseError::Json(error) => error,
+            error => panic!("unexpected error {:?}", error),
+        };
+        assert_eq!(error.pointer, "/location/lat");
+        assert_eq!((error.line, error.column), (2, 33));
+
+        // The invalid sensor makes direct deserialization fail elsewhere
+        let document = document.replace(
+            r#""space":"a""#,
+            r#""sensors":{"temperature":[{"unit":"°C","value":20.0}]},"space":"a""#,
+        );
+        let error = match from_str(&document, Mode::Lenient).unwrap_err() {
+            ParseError::Json(error) => error,
+            error => panic!("unexpected error {:?}", error),
+        };
+        assert_eq!(error.pointer, "/location/lat");
+        assert_eq!((error.line, error.column), (2, 27));
+        assert!(error
+            .snippet
+            .unwrap()
+            .starts_with(r#"        "location":{"lat":"north""#));
+    }
+
+    #[test]
+    fn offset_of_pointer() {
+        let json = r#"{"a~/b": [1, {"c": "x,]}"}, [true]], "d" : {"e":null}}"#;
+        let value_at = |pointer| offset_of(json, pointer).map(|offset| &json[offset..]);
+        assert_eq!(value_at(""), Some(json));
+        assert_eq!(
+            value_at("/a~0~1b/1/c"),
+            Some(r#""x,]}"}, [true]], "d" : {"e":null}}"#)
+        );
+        assert_eq!(value_at("/a~0~1b/2/0"), Some(r#"true]], "d" : {"e":null}}"#));
+        assert_eq!(value_at("/d/e"), Some("null}}"));
+        assert_eq!(value_at("/d/f"), None);
+        assert_eq!(value_at("/a~0~1b/3"), None);
+    }
+
+    /// `from_message` depends on the wording of serde_json's messages, so
+    /// check every kind of data error it is meant to understand.
+    #[test]
+    fn from_message_understands_serde_json() {
+        use serde::de::{Error, Unexpected};
+
+        let understood = |error: serde_json::Error| {
+            let (pointer, expected) = from_message(&error, "/a".to_owned());
+            (
+                pointer,
+                expected.expect("serde_json changed the wording of its messages"),
+            )
+        };
+        assert_eq!(
+            understood(serde_json::Error::missing_field("location")),
+            ("/a/location".to_owned(), "field `location`".to_owned())
+        );
+        assert_eq!(
+            understood(serde_json::Error::invalid_type(Unexpected::Str("north"), &"f64")),
+            ("/a".to_owned(), "f64".to_owned())
+        );
+        assert_eq!(
+            understood(serde_json::Error::invalid_value(Unexpected::Signed(-1), &"u64")),
+            ("/a".to_owned(), "u64".to_owned())
+        );
+        assert_eq!(
+            understood(serde_json::Error::invalid_length(1, &"a pair")),
+            ("/a".to_owned(), "a pair".to_owned())
+        );
+        assert_eq!(
+            understood(serde_json::Error::unknown_variant("ftp", &["email", "irc"])),
+            ("/a".to_owned(), "`email` or `irc`".to_owned())
+        );
+        assert_eq!(
+            understood(serde_json::from_str::<f64>("\n\"north\"").unwrap_err()),
+            ("/a".to_owned(), "f64".to_owned())
+        );
+        assert_eq!(
+            from_message(&serde_json::from_str::<f64>("").unwrap_err(), "/a".to_owned()),
+            ("/a".to_owned(), None)
+        );
+    }
+
+    #[test]
+    fn error_in_syntax() {
+        let error = json_error("{\"space\": \"a\",\n\"url\" \"c\"}");
+        assert_eq!(error.pointer, "");
+        assert_eq!((error.line, error.column), (2, 7));
+        assert_eq!(error.snippet.as_deref(), Some("\"url\" \"c\"}\n      ^"));
+        assert_eq!(
+            error.to_string(),
+            "invalid document: expected `:` at line 2 column 7"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Validation of raw JSON against the bundled SpaceAPI JSON schemas.
+//!
+//! This module is only available with the `schema-validation` feature. The
+//! schemas for v0.13, v14 and v15 are embedded into the crate, so no network
+//! access is needed for validation.
+//!
+//! The bundled schemas are not yet verbatim copies of the official SpaceAPI
+//! schema files. See `schemas/README.md` for their provenance.
+
+use std::sync::OnceLock;
+
+use jsonschema::{Draft, JSONSchema};
+use serde_json::Value;
+
+use crate::status::SpecVersion;
+
+const SCHEMA_0_13: &str = include_str!("../schemas/0.13.json");
+const SCHEMA_14: &str = include_str!("../schemas/14.json");
+const SCHEMA_15: &str = include_str!("../schemas/15.json");
+
+/// A single schema violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// The version whose schema was violated
+    pub version: SpecVersion,
+    /// JSON pointer to the offending value in the validated document
+    pub instance_path: String,
+    /// JSON pointer to the schema keyword that failed
+    pub schema_path: String,
+    pub message: String,
+}
+
+/// The result of validating a document with `validate_json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaReport {
+    /// The versions the document was validated against
+    pub versions: Vec<SpecVersion>,
+    /// Every schema violation found
+    pub errors: Vec<SchemaError>,
+}
+
+impl SchemaReport {
+    /// Whether the document is valid according to all schemas it was validated against.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Return the bundled JSON schema for the given version.
+pub fn schema(version: SpecVersion) -> &'static Value {
+    static SCHEMAS: OnceLock<[Value; 3]> = OnceLock::new();
+    let schemas = SCHEMAS.get_or_init(|| {
+        [SCHEMA_0_13, SCHEMA_14, SCHEMA_15]
+            .map(|schema| serde_json::from_str(schema).expect("bundled schema is valid JSON"))
+    });
+    match version {
+        SpecVersion::V0_13 => &schemas[0],
+        SpecVersion::V14 => &schemas[1],
+        SpecVersion::V15 => &schemas[2],
+    }
+}
+
+fn compiled(version: SpecVersion) -> &'static JSONSchema {
+    static COMPILED: OnceLock<[JSONSchema; 3]> = OnceLock::new();
+    let compiled = COMPILED.get_or_init(|| {
+        [
+            (SpecVersion::V0_13, Draft::Draft4),
+            (SpecVersion::V14, Draft::Draft7),
+            (SpecVersion::V15, Draft::Draft7),
+        ]
+        .map(|(version, draft)| {
+            JSONSchema::options()
+                .with_draft(draft)
+                .compile(schema(version))
+                .expect("bundled schema compiles")
+        })
+    });
+    match version {
+        SpecVersion::V0_13 => &compiled[0],
+        SpecVersion::V14 => &compiled[1],
+        SpecVersion::V15 => &compiled[2],
+    }
+}
+
+/// Return the versions a raw document declares through `api` and `api_compatibility`.
+fn declared_versions(value: &Value) -> Vec<SpecVersion> {
+    let mut versions = vec![];
+    if value.get("api").and_then(Value::as_str) == Some("0.13") {
+        versions.push(SpecVersion::V0_13);
+    }
+    let compatibility = value.get("api_compatibility").and_then(Value::as_array);
+    for version in compatibility.into_iter().flatten() {
+        let version = match version.as_str() {
+            Some("14") => SpecVersion::V14,
+            Some("15") => SpecVersion::V15,
+            _ => continue,
+        };
+        if !versions.contains(&version) {
+            versions.push(version);
+        }
+    }
+    versions.sort();
+    versions
+}
+
+/// Validate a document against the schema of a single version.
+pub fn validate_json_as(value: &Value, version: SpecVersion) -> Vec<SchemaError> {
+    match compiled(version).validate(value) {
+        Ok(()) => vec![],
+        Err(errors) => errors
+            .map(|error| SchemaError {
+                version,
+                instance_path: error.instance_path.to_string(),
+                schema_path: error.schema_path.to_string(),
+                message: error.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// Validate a document against the schemas of all versions it declares.
+///
+/// Documents that do not declare any supported version are validated
+/// against the newest schema, which reports the missing version declaration.
+pub fn validate_json(value: &Value) -> SchemaReport {
+    let mut versions = declared_versions(value);
+    if versions.is_empty() {
+        versions.push(SpecVersion::V15);
+    }
+    let errors = versions
+        .iter()
+        .flat_map(|version| validate_json_as(value, *version))
+        .collect();
+    SchemaReport { versions, errors }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::status::{Contact, IssueReportChannel, Location, State, StatusBuilder};
+    use serde_json::json;
+
+    #[test]
+    fn validate_built_documents() {
+        let builders = [
+            StatusBuilder::v0_13("foo").add_issue_report_channel(IssueReportChannel::Email),
+            StatusBuilder::v14("foo"),
+            StatusBuilder::v15("foo"),
+            StatusBuilder::mixed("foo").add_issue_report_channel(IssueReportChannel::Email),
+        ];
+        for builder in builders {
+            let status = builder
+                .logo("bar")
+                .url("foobar")
+                .location(Location::default())
+                .contact(Contact::default())
+                .state(State {
+                    open: Some(true),
+                    ..State::default()
+                })
+                .add_extension("ccc", "chaostreff")
+                .build()
+                .unwrap();
+            let report = validate_json(&serde_json::to_value(&status).unwrap());
+            assert_eq!(report.errors, vec![], "{:?}", report.versions);
+        }
+    }
+
+    #[test]
+    fn validate_invalid_document() {
+        let document = json!({
+            "api_compatibility": ["14"],
+            "space": "foo",
+            "logo": "bar",
+            "url": "foobar",
+            "location": {"lat": 91.0, "lon": 0.0},
+            "contact": {},
+            "feeds": {"blog": {"type": 1, "url": "https://example.org/feed"}},
+            "contacts": {}
+        });
+        let report = validate_json(&document);
+        assert_eq!(report.versions, vec![SpecVersion::V14]);
+        let mut paths: Vec<_> = report
+            .errors
+            .iter()
+            .map(|error| (error.instance_path.as_str(), error.schema_path.as_str()))
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                ("", "/additionalProperties"),
+                (
+                    "/feeds/blog/type",
+                    "/properties/feeds/properties/blog/properties/type/type"
+                ),
+                ("/location/lat", "/properties/location/properties/lat/maximum"),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_undeclared_version() {
+        let report = validate_json(&json!({"space": "foo"}));
+        assert_eq!(report.versions, vec![SpecVersion::V15]);
+        assert!(!report.is_valid());
+    }
+}
@@ -1,9 +1,13 @@
 //! Module providing account balance sensor functionality.
 
-use super::{FromSensorTemplate, SensorMetadata, SensorTemplate, SensorTemplateError, Sensors};
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadata, SensorTemplate, SensorTemplateError,
+    SensorValue, Sensors,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AccountBalanceSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadata,
@@ -11,30 +15,45 @@ pub struct AccountBalanceSensor {
     pub value: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct AccountBalanceSensorTemplate {
+    #[serde(flatten)]
     pub metadata: SensorMetadata,
     pub unit: String,
 }
 
 impl FromSensorTemplate<AccountBalanceSensorTemplate> for AccountBalanceSensor {
-    fn try_from_template(
+    fn try_from_template_with(
         template: &AccountBalanceSensorTemplate,
-        value: &str,
+        value: &SensorValue,
+        clock: &dyn Clock,
     ) -> Result<Self, SensorTemplateError> {
-        Ok(Self {
-            metadata: template.metadata.clone(),
-            unit: template.unit.clone(),
-            value: value.parse()?,
-        })
+        with_context(
+            SensorKind::AccountBalance,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    unit: template.unit.clone(),
+                    value: value.parse()?,
+                })
+            },
+        )
     }
 }
 
 impl SensorTemplate for AccountBalanceSensorTemplate {
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
         sensors
             .account_balance
-            .push(AccountBalanceSensor::try_from_template(self, value_str)?);
+            .push(AccountBalanceSensor::try_from_template_with(self, value, clock)?);
         Ok(())
     }
 }
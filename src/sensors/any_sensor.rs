@@ -0,0 +1,129 @@
+//! Module providing a single type able to hold any sensor.
+
+use super::{
+    AccountBalanceSensor, BarometerSensor, BeverageSupplySensor, CarbonDioxideSensor, DoorLockedSensor,
+    GasConcentrationSensor, HumiditySensor, NetworkConnectionsSensor, NetworkTrafficSensor,
+    ParticulateMatterSensor, PeopleNowPresentSensor, PowerConsumptionSensor, PowerGenerationSensor,
+    RadiationSensor, RadiationSensorKind, Sensors, TemperatureSensor, TotalMemberCountSensor, WindSensor,
+};
+
+/// An instance of any sensor type.
+///
+/// Every sensor struct converts into an `AnySensor` through `From`, radiation
+/// sensors additionally need the kind of radiation they measure.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnySensor {
+    Temperature(TemperatureSensor),
+    DoorLocked(DoorLockedSensor),
+    Barometer(BarometerSensor),
+    Radiation(RadiationSensorKind, RadiationSensor),
+    Humidity(HumiditySensor),
+    BeverageSupply(BeverageSupplySensor),
+    PowerConsumption(PowerConsumptionSensor),
+    Wind(WindSensor),
+    NetworkConnections(NetworkConnectionsSensor),
+    AccountBalance(AccountBalanceSensor),
+    TotalMemberCount(TotalMemberCountSensor),
+    PeopleNowPresent(PeopleNowPresentSensor),
+    NetworkTraffic(NetworkTrafficSensor),
+    CarbonDioxide(CarbonDioxideSensor),
+    PowerGeneration(PowerGenerationSensor),
+    ParticulateMatter(ParticulateMatterSensor),
+    GasConcentration(GasConcentrationSensor),
+}
+
+/// Implement `From<$sensor> for AnySensor` for the given variants.
+macro_rules! impl_from_sensor {
+    ($($variant:ident($sensor:ty)),* $(,)?) => {
+        $(
+            impl From<$sensor> for AnySensor {
+                fn from(sensor: $sensor) -> Self {
+                    AnySensor::$variant(sensor)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_sensor!(
+    Temperature(TemperatureSensor),
+    DoorLocked(DoorLockedSensor),
+    Barometer(BarometerSensor),
+    Humidity(HumiditySensor),
+    BeverageSupply(BeverageSupplySensor),
+    PowerConsumption(PowerConsumptionSensor),
+    Wind(WindSensor),
+    NetworkConnections(NetworkConnectionsSensor),
+    AccountBalance(AccountBalanceSensor),
+    TotalMemberCount(TotalMemberCountSensor),
+    PeopleNowPresent(PeopleNowPresentSensor),
+    NetworkTraffic(NetworkTrafficSensor),
+    CarbonDioxide(CarbonDioxideSensor),
+    PowerGeneration(PowerGenerationSensor),
+    ParticulateMatter(ParticulateMatterSensor),
+    GasConcentration(GasConcentrationSensor),
+);
+
+impl From<(RadiationSensorKind, RadiationSensor)> for AnySensor {
+    fn from((kind, sensor): (RadiationSensorKind, RadiationSensor)) -> Self {
+        AnySensor::Radiation(kind, sensor)
+    }
+}
+
+impl Sensors {
+    /// Add a sensor to the list of its kind.
+    pub fn push<S: Into<AnySensor>>(&mut self, sensor: S) {
+        match sensor.into() {
+            AnySensor::Temperature(sensor) => self.temperature.push(sensor),
+            AnySensor::DoorLocked(sensor) => self.door_locked.push(sensor),
+            AnySensor::Barometer(sensor) => self.barometer.push(sensor),
+            AnySensor::Radiation(kind, sensor) => self
+                .radiation
+                .get_or_insert_with(Default::default)
+                .push(kind, sensor),
+            AnySensor::Humidity(sensor) => self.humidity.push(sensor),
+            AnySensor::BeverageSupply(sensor) => self.beverage_supply.push(sensor),
+            AnySensor::PowerConsumption(sensor) => self.power_consumption.push(sensor),
+            AnySensor::Wind(sensor) => self.wind.push(sensor),
+            AnySensor::NetworkConnections(sensor) => self.network_connections.push(sensor),
+            AnySensor::AccountBalance(sensor) => self.account_balance.push(sensor),
+            AnySensor::TotalMemberCount(sensor) => self.total_member_count.push(sensor),
+            AnySensor::PeopleNowPresent(sensor) => self.people_now_present.push(sensor),
+            AnySensor::NetworkTraffic(sensor) => self.network_traffic.push(sensor),
+            AnySensor::CarbonDioxide(sensor) => self.carbondioxide.push(sensor),
+            AnySensor::PowerGeneration(sensor) => self.power_generation.push(sensor),
+            AnySensor::ParticulateMatter(sensor) => self.particulate_matter.push(sensor),
+            AnySensor::GasConcentration(sensor) => self.gas_concentration.push(sensor),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::{RadiationSensorUnit, SensorMetadata};
+
+    #[test]
+    fn push_sensors() {
+        let mut sensors = Sensors::default();
+        sensors.push(TotalMemberCountSensor {
+            value: 42,
+            ..Default::default()
+        });
+        sensors.push((
+            RadiationSensorKind::Gamma,
+            RadiationSensor {
+                metadata: SensorMetadata::default(),
+                dead_time: None,
+                conversion_factor: None,
+                unit: RadiationSensorUnit::CountsPerMinute,
+                value: 12.0,
+            },
+        ));
+
+        assert_eq!(
+            "{\"radiation\":{\"gamma\":[{\"unit\":\"cpm\",\"value\":12.0}]},\"total_member_count\":[{\"value\":42}]}",
+            serde_json::to_string(&sensors).unwrap()
+        );
+    }
+}
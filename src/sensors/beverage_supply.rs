@@ -1,9 +1,13 @@
 //! Module providing beverage supply sensor functionality.
 
-use super::{FromSensorTemplate, SensorMetadata, SensorTemplate, SensorTemplateError, Sensors};
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadata, SensorTemplate, SensorTemplateError,
+    SensorValue, Sensors,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BeverageSupplySensor {
     #[serde(flatten)]
     pub metadata: SensorMetadata,
@@ -11,30 +15,45 @@ pub struct BeverageSupplySensor {
     pub value: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct BeverageSupplySensorTemplate {
+    #[serde(flatten)]
     pub metadata: SensorMetadata,
     pub unit: String,
 }
 
 impl FromSensorTemplate<BeverageSupplySensorTemplate> for BeverageSupplySensor {
-    fn try_from_template(
+    fn try_from_template_with(
         template: &BeverageSupplySensorTemplate,
-        value: &str,
+        value: &SensorValue,
+        clock: &dyn Clock,
     ) -> Result<Self, SensorTemplateError> {
-        Ok(Self {
-            metadata: template.metadata.clone(),
-            unit: template.unit.clone(),
-            value: value.parse()?,
-        })
+        with_context(
+            SensorKind::BeverageSupply,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    unit: template.unit.clone(),
+                    value: value.parse()?,
+                })
+            },
+        )
     }
 }
 
 impl SensorTemplate for BeverageSupplySensorTemplate {
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
         sensors
             .beverage_supply
-            .push(BeverageSupplySensor::try_from_template(self, value_str)?);
+            .push(BeverageSupplySensor::try_from_template_with(self, value, clock)?);
         Ok(())
     }
 }
@@ -0,0 +1,98 @@
+//! Module providing carbon dioxide sensor functionality.
+
+use super::value::check_range;
+use super::{
+    with_context, CarbonDioxideUnit, Clock, FromSensorTemplate, SensorKind, SensorMetadataWithLocation,
+    SensorTemplate, SensorTemplateError, SensorValue, Sensors,
+};
+use serde::{Deserialize, Serialize};
+
+/// A carbon dioxide sensor, introduced in v14.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CarbonDioxideSensor {
+    #[serde(flatten)]
+    pub metadata: SensorMetadataWithLocation,
+    pub unit: CarbonDioxideUnit,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CarbonDioxideSensorTemplate {
+    #[serde(flatten)]
+    pub metadata: SensorMetadataWithLocation,
+    #[serde(default)]
+    pub unit: CarbonDioxideUnit,
+}
+
+impl FromSensorTemplate<CarbonDioxideSensorTemplate> for CarbonDioxideSensor {
+    fn try_from_template_with(
+        template: &CarbonDioxideSensorTemplate,
+        value: &SensorValue,
+        clock: &dyn Clock,
+    ) -> Result<Self, SensorTemplateError> {
+        with_context(
+            SensorKind::CarbonDioxide,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    unit: template.unit.clone(),
+                    value: check_range(value.parse()?, 0.0..=f64::MAX, "at least 0")?,
+                })
+            },
+        )
+    }
+}
+
+impl SensorTemplate for CarbonDioxideSensorTemplate {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
+        sensors
+            .carbondioxide
+            .push(CarbonDioxideSensor::try_from_template_with(self, value, clock)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::{test_clock, SensorValueError};
+    use serde_json::{from_str, to_string};
+
+    #[test]
+    fn serialize_deserialize() {
+        let json = "[{\"name\":\"Air\",\"location\":\"Hall\",\"unit\":\"ppm\",\"value\":812.0}]";
+        let sensors: Vec<CarbonDioxideSensor> = from_str(json).unwrap();
+        assert_eq!(sensors[0].unit, CarbonDioxideUnit::PartsPerMillion);
+        assert_eq!(to_string(&sensors).unwrap(), json);
+    }
+
+    #[test]
+    fn test_template() {
+        let template = CarbonDioxideSensorTemplate {
+            metadata: SensorMetadataWithLocation {
+                location: "Hall".into(),
+                ..Default::default()
+            },
+            unit: CarbonDioxideUnit::PartsPerMillion,
+        };
+
+        let mut sensors = Sensors::default();
+        template.to_sensor_with("812", &mut sensors, &test_clock());
+        assert_eq!(
+            "[{\"location\":\"Hall\",\"lastchange\":1700000000,\"unit\":\"ppm\",\"value\":812.0}]",
+            to_string(&sensors.carbondioxide).unwrap()
+        );
+
+        let error = template.try_to_sensor("-1", &mut sensors).unwrap_err();
+        assert!(matches!(error.error, SensorValueError::OutOfRange { .. }));
+    }
+}
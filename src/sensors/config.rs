@@ -0,0 +1,329 @@
+//! Module providing sensor templates defined in configuration files.
+
+use std::collections::btree_map::{BTreeMap, Entry};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{
+    AccountBalanceSensorTemplate, BarometerSensorTemplate, BeverageSupplySensorTemplate,
+    CarbonDioxideSensorTemplate, DoorLockedSensorTemplate, GasConcentrationSensorTemplate,
+    HumiditySensorTemplate, NetworkConnectionsSensorTemplate, NetworkTrafficSensorTemplate,
+    ParticulateMatterSensorTemplate, PeopleNowPresentSensorTemplate, PowerConsumptionSensorTemplate,
+    PowerGenerationSensorTemplate, RadiationSensorTemplate, SensorKey, SensorTemplate, SensorTemplateError,
+    SensorValue, Sensors, TemperatureSensorTemplate, TotalMemberCountSensorTemplate, WindSensorTemplate,
+};
+
+/// Sensor templates grouped by kind, each identified by an id.
+///
+/// The ids are the keys of the tables of each kind and must be unique across
+/// all kinds. Tables of unknown kinds are rejected. The fields of a template
+/// are the fields of its template struct, with the metadata inlined:
+///
+/// ```toml
+/// [temperature.hall]
+/// location = "Hall"
+/// unit = "°C"
+///
+/// [radiation.geiger]
+/// kind = "beta_gamma"
+/// unit = "cpm"
+/// conversion_factor = 0.0057
+/// ```
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SensorConfig {
+    pub temperature: BTreeMap<String, TemperatureSensorTemplate>,
+    pub door_locked: BTreeMap<String, DoorLockedSensorTemplate>,
+    pub barometer: BTreeMap<String, BarometerSensorTemplate>,
+    pub radiation: BTreeMap<String, RadiationSensorTemplate>,
+    pub humidity: BTreeMap<String, HumiditySensorTemplate>,
+    pub beverage_supply: BTreeMap<String, BeverageSupplySensorTemplate>,
+    pub power_consumption: BTreeMap<String, PowerConsumptionSensorTemplate>,
+    pub wind: BTreeMap<String, WindSensorTemplate>,
+    pub network_connections: BTreeMap<String, NetworkConnectionsSensorTemplate>,
+    pub account_balance: BTreeMap<String, AccountBalanceSensorTemplate>,
+    pub total_member_count: BTreeMap<String, TotalMemberCountSensorTemplate>,
+    pub people_now_present: BTreeMap<String, PeopleNowPresentSensorTemplate>,
+    pub network_traffic: BTreeMap<String, NetworkTrafficSensorTemplate>,
+    pub carbondioxide: BTreeMap<String, CarbonDioxideSensorTemplate>,
+    pub power_generation: BTreeMap<String, PowerGenerationSensorTemplate>,
+    pub particulate_matter: BTreeMap<String, ParticulateMatterSensorTemplate>,
+    pub gas_concentration: BTreeMap<String, GasConcentrationSensorTemplate>,
+}
+
+/// Describes an error occurring when loading a `SensorConfig`.
+#[derive(Error, Debug)]
+pub enum SensorConfigError {
+    /// The configuration is not valid JSON or does not describe sensor templates
+    #[error("sensor configuration cannot be decoded")]
+    Json(#[from] serde_json::Error),
+
+    /// The configuration is not valid TOML or does not describe sensor templates
+    #[cfg(feature = "toml")]
+    #[error("sensor configuration cannot be decoded")]
+    Toml(#[from] toml::de::Error),
+
+    /// The same id is used for templates of several kinds
+    #[error("sensor id {0:?} is defined more than once")]
+    DuplicateId(String),
+}
+
+/// Describes an error occurring when feeding a value to a `SensorRegistry`.
+#[derive(Error, Debug)]
+pub enum SensorRegistryError {
+    /// No template is registered under the given id
+    #[error("no sensor with id {0:?}")]
+    UnknownId(String),
+
+    /// The template rejected the value
+    #[error(transparent)]
+    Template(#[from] SensorTemplateError),
+}
+
+impl SensorConfig {
+    /// Load a configuration from JSON.
+    pub fn from_json(json: &str) -> Result<Self, SensorConfigError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load a configuration from TOML.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self, SensorConfigError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Turn the configuration into a registry of its templates.
+    ///
+    /// Fails if an id is used for templates of several kinds.
+    pub fn into_registry(self) -> Result<SensorRegistry, SensorConfigError> {
+        let mut registry = SensorRegistry::default();
+        registry.extend(self.temperature)?;
+        registry.extend(self.door_locked)?;
+        registry.extend(self.barometer)?;
+        registry.extend(self.radiation)?;
+        registry.extend(self.humidity)?;
+        registry.extend(self.beverage_supply)?;
+        registry.extend(self.power_consumption)?;
+        registry.extend(self.wind)?;
+        registry.extend(self.network_connections)?;
+        registry.extend(self.account_balance)?;
+        registry.extend(self.total_member_count)?;
+        registry.extend(self.people_now_present)?;
+        registry.extend(self.network_traffic)?;
+        registry.extend(self.carbondioxide)?;
+        registry.extend(self.power_generation)?;
+        registry.extend(self.particulate_matter)?;
+        registry.extend(self.gas_concentration)?;
+        Ok(registry)
+    }
+}
+
+/// Sensor templates identified by id, usually loaded from a `SensorConfig`.
+#[derive(Default)]
+pub struct SensorRegistry {
+    templates: BTreeMap<String, Box<dyn SensorTemplate>>,
+}
+
+impl SensorRegistry {
+    /// Register a template under the given id, returning the template it replaces.
+    pub fn insert<S: Into<String>>(
+        &mut self,
+        id: S,
+        template: Box<dyn SensorTemplate>,
+    ) -> Option<Box<dyn SensorTemplate>> {
+        self.templates.insert(id.into(), template)
+    }
+
+    fn extend<T: SensorTemplate + 'static>(
+        &mut self,
+        templates: BTreeMap<String, T>,
+    ) -> Result<(), SensorConfigError> {
+        for (id, template) in templates {
+            match self.templates.entry(id) {
+                Entry::Occupied(entry) => return Err(SensorConfigError::DuplicateId(entry.key().clone())),
+                Entry::Vacant(entry) => {
+                    entry.insert(Box::new(template));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the template with the given id.
+    pub fn get(&self, id: &str) -> Option<&dyn SensorTemplate> {
+        self.templates.get(id).map(Box::as_ref)
+    }
+
+    /// Iterate over all ids in ascending order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    /// Return the number of registered templates.
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Return `true` if no template is registered.
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Build the sensor with the given id from `value` and add it to `sensors`.
+    ///
+    /// A sensor with the same kind, name and location that is already present
+    /// is replaced like with `Sensors::upsert`, so the same id can be fed
+    /// repeatedly.
+    pub fn set_value<V: Into<SensorValue>>(
+        &self,
+        id: &str,
+        value: V,
+        sensors: &mut Sensors,
+    ) -> Result<(), SensorRegistryError> {
+        let template = self
+            .get(id)
+            .ok_or_else(|| SensorRegistryError::UnknownId(id.to_owned()))?;
+        let mut built = Sensors::default();
+        template.try_to_sensor_value(&value.into(), &mut built)?;
+        let keys: Vec<_> = built
+            .iter()
+            .map(|(kind, sensor)| (kind, SensorKey::of(sensor)))
+            .collect();
+        for (kind, key) in keys {
+            if let Some(sensor) = built.remove(kind, &key) {
+                sensors.upsert(sensor);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::{RadiationSensorKind, SensorKind, TemperatureUnit};
+
+    const JSON: &str = r#"{
+        "temperature": {
+            "hall": {"location": "Hall", "unit": "°F"},
+            "lab": {"location": "Lab", "name": "Bench"}
+        },
+        "radiation": {
+            "geiger": {"kind": "beta_gamma", "unit": "cpm", "conversion_factor": 0.0057}
+        },
+        "network_connections": {
+            "wifi": {"type": "wifi"}
+        }
+    }"#;
+
+    #[test]
+    fn load_json_config() {
+        let config = SensorConfig::from_json(JSON).unwrap();
+        assert_eq!(config.temperature["hall"].unit, TemperatureUnit::Fahrenheit);
+        assert_eq!(config.temperature["lab"].unit, TemperatureUnit::Celsius);
+        assert_eq!(config.temperature["lab"].metadata.name, Some("Bench".into()));
+        assert_eq!(config.radiation["geiger"].kind, RadiationSensorKind::BetaGamma);
+
+        let registry = config.into_registry().unwrap();
+        assert_eq!(
+            registry.ids().collect::<Vec<_>>(),
+            ["geiger", "hall", "lab", "wifi"]
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn load_toml_config() {
+        let config = SensorConfig::from_toml(
+            r#"
+            [temperature.hall]
+            location = "Hall"
+            unit = "°C"
+
+            [wind.roof]
+            location = "Roof"
+            speed_unit = "km/h"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.temperature["hall"].metadata.location, "Hall");
+        assert_eq!(config.into_registry().unwrap().len(), 2);
+
+        assert!(matches!(
+            SensorConfig::from_toml("[temperature.hall]\nunit = \"°C\""),
+            Err(SensorConfigError::Toml(_))
+        ));
+        assert!(matches!(
+            SensorConfig::from_toml("[temprature.hall]\nlocation = \"Hall\""),
+            Err(SensorConfigError::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_kinds() {
+        for json in [
+            r#"{"temprature": {"hall": {"location": "Hall"}}}"#,
+            r#"{"carbon_dioxide": {"x": {"location": "Hall"}}}"#,
+        ] {
+            assert!(matches!(
+                SensorConfig::from_json(json),
+                Err(SensorConfigError::Json(error)) if error.to_string().starts_with("unknown field")
+            ));
+        }
+    }
+
+    #[test]
+    fn duplicate_ids() {
+        let config = SensorConfig::from_json(
+            r#"{"door_locked": {"front": {"location": "Front"}},
+                "people_now_present": {"front": {}}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            config.into_registry(),
+            Err(SensorConfigError::DuplicateId(id)) if id == "front"
+        ));
+    }
+
+    #[test]
+    fn feed_values_by_id() {
+        let registry = SensorConfig::from_json(JSON).unwrap().into_registry().unwrap();
+        let mut sensors = Sensors::default();
+        registry.set_value("hall", "70.1", &mut sensors).unwrap();
+        registry.set_value("lab", "20.5", &mut sensors).unwrap();
+        registry.set_value("hall", "71.6", &mut sensors).unwrap();
+        registry.set_value("geiger", "18", &mut sensors).unwrap();
+
+        let values: Vec<_> = sensors
+            .iter()
+            .map(|(kind, sensor)| (kind, sensor.location().map(str::to_owned), sensor.value()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                (SensorKind::Temperature, Some("Hall".into()), Some(71.6)),
+                (SensorKind::Temperature, Some("Lab".into()), Some(20.5)),
+                (
+                    SensorKind::Radiation(RadiationSensorKind::BetaGamma),
+                    None,
+                    Some(18.0)
+                ),
+            ]
+        );
+
+        sensors.temperature[0].metadata.lastchange = Some(u64::MAX);
+        registry.set_value("hall", "72.0", &mut sensors).unwrap();
+        assert_eq!(sensors.temperature[0].value, 72.0);
+        assert!(sensors.temperature[0].metadata.lastchange < Some(u64::MAX));
+
+        assert!(matches!(
+            registry.set_value("cellar", "12", &mut sensors),
+            Err(SensorRegistryError::UnknownId(id)) if id == "cellar"
+        ));
+        assert!(matches!(
+            registry.set_value("wifi", "many", &mut sensors),
+            Err(SensorRegistryError::Template(_))
+        ));
+    }
+}
@@ -1,37 +1,50 @@
 //! Module providing door lock sensor functionality.
 
-use super::{FromSensorTemplate, SensorMetadataWithLocation, SensorTemplate, SensorTemplateError, Sensors};
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadataWithLocation, SensorTemplate,
+    SensorTemplateError, SensorValue, Sensors,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DoorLockedSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadataWithLocation,
     pub value: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct DoorLockedSensorTemplate {
+    #[serde(flatten)]
     pub metadata: SensorMetadataWithLocation,
 }
 
 impl FromSensorTemplate<DoorLockedSensorTemplate> for DoorLockedSensor {
-    fn try_from_template(
+    fn try_from_template_with(
         template: &DoorLockedSensorTemplate,
-        value: &str,
+        value: &SensorValue,
+        clock: &dyn Clock,
     ) -> Result<Self, SensorTemplateError> {
-        Ok(Self {
-            metadata: template.metadata.clone(),
-            value: value.parse()?,
+        with_context(SensorKind::DoorLocked, &template.metadata, value, clock, || {
+            Ok(Self {
+                metadata: template.metadata.clone(),
+                value: value.parse()?,
+            })
         })
     }
 }
 
 impl SensorTemplate for DoorLockedSensorTemplate {
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
         sensors
             .door_locked
-            .push(DoorLockedSensor::try_from_template(self, value_str)?);
+            .push(DoorLockedSensor::try_from_template_with(self, value, clock)?);
         Ok(())
     }
 }
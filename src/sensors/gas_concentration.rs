@@ -0,0 +1,91 @@
+//! Module providing gas concentration sensor functionality.
+
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadataWithLocation, SensorTemplate,
+    SensorTemplateError, SensorValue, Sensors,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GasConcentrationSensor {
+    #[serde(flatten)]
+    pub metadata: SensorMetadataWithLocation,
+    pub gas: String,
+    pub unit: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GasConcentrationSensorTemplate {
+    #[serde(flatten)]
+    pub metadata: SensorMetadataWithLocation,
+    pub gas: String,
+    pub unit: String,
+}
+
+impl FromSensorTemplate<GasConcentrationSensorTemplate> for GasConcentrationSensor {
+    fn try_from_template_with(
+        template: &GasConcentrationSensorTemplate,
+        value: &SensorValue,
+        clock: &dyn Clock,
+    ) -> Result<Self, SensorTemplateError> {
+        with_context(
+            SensorKind::GasConcentration,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    gas: template.gas.clone(),
+                    unit: template.unit.clone(),
+                    value: value.parse()?,
+                })
+            },
+        )
+    }
+}
+
+impl SensorTemplate for GasConcentrationSensorTemplate {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
+        sensors
+            .gas_concentration
+            .push(GasConcentrationSensor::try_from_template_with(
+                self, value, clock,
+            )?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::test_clock;
+
+    #[test]
+    fn test_template() {
+        let template = GasConcentrationSensorTemplate {
+            metadata: SensorMetadataWithLocation {
+                location: "Kitchen".into(),
+                ..Default::default()
+            },
+            gas: "CO".into(),
+            unit: "ppm".into(),
+        };
+
+        let mut sensors = Sensors::default();
+        template.to_sensor_with("2.5", &mut sensors, &test_clock());
+
+        assert_eq!(
+            "[{\"location\":\"Kitchen\",\"lastchange\":1700000000,\"gas\":\"CO\",\"unit\":\"ppm\",\"value\":2.5}]",
+            serde_json::to_string(&sensors.gas_concentration).unwrap()
+        );
+        assert!(template.try_to_sensor("a lot", &mut sensors).is_err());
+    }
+}
@@ -1,40 +1,81 @@
 //! Module providing humidity sensor functionality.
 
-use super::{FromSensorTemplate, SensorMetadataWithLocation, SensorTemplate, SensorTemplateError, Sensors};
+use super::value::check_range;
+use super::{
+    with_context, Clock, FromSensorTemplate, HumidityUnit, SensorKind, SensorMetadataWithLocation,
+    SensorTemplate, SensorTemplateError, SensorValue, Sensors,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct HumiditySensor {
     #[serde(flatten)]
     pub metadata: SensorMetadataWithLocation,
-    pub unit: String,
+    pub unit: HumidityUnit,
     pub value: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct HumiditySensorTemplate {
+    #[serde(flatten)]
     pub metadata: SensorMetadataWithLocation,
-    pub unit: String,
+    #[serde(default)]
+    pub unit: HumidityUnit,
 }
 
 impl FromSensorTemplate<HumiditySensorTemplate> for HumiditySensor {
-    fn try_from_template(
+    fn try_from_template_with(
         template: &HumiditySensorTemplate,
-        value: &str,
+        value: &SensorValue,
+        clock: &dyn Clock,
     ) -> Result<Self, SensorTemplateError> {
-        Ok(Self {
-            metadata: template.metadata.clone(),
-            unit: template.unit.clone(),
-            value: value.parse()?,
+        with_context(SensorKind::Humidity, &template.metadata, value, clock, || {
+            Ok(Self {
+                metadata: template.metadata.clone(),
+                unit: template.unit.clone(),
+                value: check_range(value.parse()?, 0.0..=100.0, "between 0 and 100")?,
+            })
         })
     }
 }
 
 impl SensorTemplate for HumiditySensorTemplate {
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
         sensors
             .humidity
-            .push(HumiditySensor::try_from_template(self, value_str)?);
+            .push(HumiditySensor::try_from_template_with(self, value, clock)?);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_template_out_of_range() {
+        let template = HumiditySensorTemplate {
+            metadata: SensorMetadataWithLocation {
+                location: "Basement".into(),
+                ..Default::default()
+            },
+            unit: HumidityUnit::Percent,
+        };
+
+        let mut sensors = Sensors::default();
+        template.to_sensor("55.5", &mut sensors);
+        let result = template.try_to_sensor("101", &mut sensors);
+
+        assert_eq!(
+            "humidity sensor at \"Basement\" rejected input \"101\": sensor value 101 must be between 0 and 100",
+            result.err().unwrap().to_string()
+        );
+        assert_eq!(sensors.humidity.len(), 1);
+    }
+}
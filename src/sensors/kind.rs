@@ -0,0 +1,62 @@
+//! Module providing the kinds of sensors.
+
+use std::fmt;
+
+use super::RadiationSensorKind;
+
+/// The kind of a sensor, i.e. the key of its list in `Sensors`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SensorKind {
+    Temperature,
+    DoorLocked,
+    Barometer,
+    Radiation(RadiationSensorKind),
+    Humidity,
+    BeverageSupply,
+    PowerConsumption,
+    Wind,
+    NetworkConnections,
+    AccountBalance,
+    TotalMemberCount,
+    PeopleNowPresent,
+    NetworkTraffic,
+    CarbonDioxide,
+    PowerGeneration,
+    ParticulateMatter,
+    GasConcentration,
+}
+
+impl SensorKind {
+    /// The key of the sensor list in `Sensors`, e.g. `door_locked`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "temperature",
+            SensorKind::DoorLocked => "door_locked",
+            SensorKind::Barometer => "barometer",
+            SensorKind::Radiation(_) => "radiation",
+            SensorKind::Humidity => "humidity",
+            SensorKind::BeverageSupply => "beverage_supply",
+            SensorKind::PowerConsumption => "power_consumption",
+            SensorKind::Wind => "wind",
+            SensorKind::NetworkConnections => "network_connections",
+            SensorKind::AccountBalance => "account_balance",
+            SensorKind::TotalMemberCount => "total_member_count",
+            SensorKind::PeopleNowPresent => "people_now_present",
+            SensorKind::NetworkTraffic => "network_traffic",
+            SensorKind::CarbonDioxide => "carbondioxide",
+            SensorKind::PowerGeneration => "power_generation",
+            SensorKind::ParticulateMatter => "particulate_matter",
+            SensorKind::GasConcentration => "gas_concentration",
+        }
+    }
+}
+
+impl fmt::Display for SensorKind {
+    /// Formats the dotted path of the sensor list, e.g. `radiation.beta_gamma`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SensorKind::Radiation(kind) => write!(f, "radiation.{}", kind.as_str()),
+            kind => f.write_str(kind.as_str()),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Module providing the combination of sensors from several sources.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{RadiationSensorKind, Sensor, SensorKey, SensorKind, Sensors};
+
+/// How `Sensors::merge` handles a sensor that is present in both collections.
+///
+/// Sensors are the same if they have the same kind and `SensorKey`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MergeStrategy {
+    /// Keep the sensor with the newer `lastchange`. Sensors without
+    /// `lastchange` are older than all others, and on ties the sensor being
+    /// merged in wins.
+    #[default]
+    NewestWins,
+    /// Keep both sensors, the existing one first.
+    KeepBoth,
+    /// Fail with a `MergeConflict` without modifying the collection.
+    Error,
+}
+
+/// Describes a conflict found by `Sensors::merge` with `MergeStrategy::Error`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MergeConflict {
+    /// The sensor would be added twice, because it is present in both
+    /// collections or more than once in the one being merged in
+    #[error("{kind} sensor {key} is present more than once")]
+    Sensor { kind: SensorKind, key: SensorKey },
+    /// The unknown sensor kind is present in both collections
+    #[error("unknown sensor kind {0:?} is present in both collections")]
+    UnknownKind(String),
+}
+
+/// Merge `other` into `list` and sort the result.
+///
+/// Sensors are sorted by key, then by `lastchange` and finally by their JSON,
+/// so sensors with the same key end up in the same order whichever list they
+/// came from.
+fn merge_list<S: Sensor + Serialize>(list: &mut Vec<S>, other: Vec<S>, strategy: MergeStrategy) {
+    for sensor in other {
+        let key = SensorKey::of(&sensor);
+        let existing = match strategy {
+            MergeStrategy::KeepBoth => None,
+            MergeStrategy::NewestWins | MergeStrategy::Error => {
+                list.iter_mut().find(|existing| key.matches(*existing))
+            }
+        };
+        match existing {
+            Some(existing) => {
+                if sensor.lastchange() >= existing.lastchange() {
+                    *existing = sensor;
+                }
+            }
+            None => list.push(sensor),
+        }
+    }
+    list.sort_by_cached_key(|sensor| {
+        (
+            SensorKey::of(sensor),
+            sensor.lastchange(),
+            serde_json::to_string(sensor).ok(),
+        )
+    });
+}
+
+impl Sensors {
+    /// Add all sensors of `other` to this collection.
+    ///
+    /// Sensors with the same kind and key are handled according to
+    /// `strategy`. Afterwards the sensors of every kind are sorted by name
+    /// and location, and sensors with the same name and location by
+    /// `lastchange` and then by content. With `MergeStrategy::KeepBoth` the
+    /// result is therefore the same whichever collection is merged into the
+    /// other. With `MergeStrategy::Error` the first sensor of `other` that is
+    /// already present, or that `other` itself contains more than once, is
+    /// returned and the collection is left untouched.
+    ///
+    /// Sensors of unknown kinds in `Sensors::other` cannot be told apart, so
+    /// every unknown kind present in both collections is a conflict with
+    /// `MergeStrategy::Error`. With the other strategies, lists of unknown
+    /// kinds are concatenated and other values of unknown kinds are replaced
+    /// by those of `other`.
+    pub fn merge(&mut self, other: Sensors, strategy: MergeStrategy) -> Result<(), MergeConflict> {
+        if strategy == MergeStrategy::Error {
+            let mut seen = HashSet::new();
+            let conflict = other.iter().find(|(kind, sensor)| {
+                self.find(*kind, sensor.name(), sensor.location()).is_some()
+                    || !seen.insert((*kind, SensorKey::of(*sensor)))
+            });
+            if let Some((kind, sensor)) = conflict {
+                return Err(MergeConflict::Sensor {
+                    kind,
+                    key: SensorKey::of(sensor),
+                });
+            }
+            if let Some(kind) = other.other.keys().find(|kind| self.other.contains_key(*kind)) {
+                return Err(MergeConflict::UnknownKind(kind.clone()));
+            }
+        }
+
+        merge_list(&mut self.temperature, other.temperature, strategy);
+        merge_list(&mut self.door_locked, other.door_locked, strategy);
+        merge_list(&mut self.barometer, other.barometer, strategy);
+        if let Some(mut other_radiation) = other.radiation {
+            let radiation = self.radiation.get_or_insert_with(Default::default);
+            for kind in [
+                RadiationSensorKind::Alpha,
+                RadiationSensorKind::Beta,
+                RadiationSensorKind::Gamma,
+                RadiationSensorKind::BetaGamma,
+            ] {
+                if let Some(other_list) = other_radiation.get_mut(kind).take() {
+                    let list = radiation.get_mut(kind).get_or_insert_with(Vec::new);
+                    merge_list(list, other_list, strategy);
+                }
+            }
+        }
+        merge_list(&mut self.humidity, other.humidity, strategy);
+        merge_list(&mut self.beverage_supply, other.beverage_supply, strategy);
+        merge_list(&mut self.power_consumption, other.power_consumption, strategy);
+        merge_list(&mut self.wind, other.wind, strategy);
+        merge_list(&mut self.network_connections, other.network_connections, strategy);
+        merge_list(&mut self.account_balance, other.account_balance, strategy);
+        merge_list(&mut self.total_member_count, other.total_member_count, strategy);
+        merge_list(&mut self.people_now_present, other.people_now_present, strategy);
+        merge_list(&mut self.network_traffic, other.network_traffic, strategy);
+        merge_list(&mut self.carbondioxide, other.carbondioxide, strategy);
+        merge_list(&mut self.power_generation, other.power_generation, strategy);
+        merge_list(&mut self.particulate_matter, other.particulate_matter, strategy);
+        merge_list(&mut self.gas_concentration, other.gas_concentration, strategy);
+        for (key, value) in other.other {
+            match (self.other.get_mut(&key), value) {
+                (Some(Value::Array(list)), Value::Array(other_list)) => list.extend(other_list),
+                (_, value) => {
+                    self.other.insert(key, value);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::{
+        DoorLockedSensor, RadiationSensor, RadiationSensorUnit, SensorMetadata, SensorMetadataWithLocation,
+        TemperatureSensor, TemperatureUnit,
+    };
+
+    fn temperature(location: &str, value: f64, lastchange: Option<u64>) -> TemperatureSensor {
+        TemperatureSensor {
+            metadata: SensorMetadataWithLocation {
+                location: location.into(),
+                lastchange,
+                ..Default::default()
+            },
+            unit: TemperatureUnit::Celsius,
+            value,
+        }
+    }
+
+    fn collections() -> (Sensors, Sensors) {
+        let mut door_controller = Sensors::default();
+        door_controller.push(temperature("Lab", 21.0, Some(200)));
+        door_controller.push(temperature("Hall", 19.0, Some(100)));
+        door_controller.push(DoorLockedSensor {
+            metadata: SensorMetadataWithLocation {
+                location: "Front door".into(),
+                ..Default::default()
+            },
+            value: true,
+        });
+
+        let mut weather_station = Sensors::default();
+        weather_station.push(temperature("Roof", 8.0, Some(150)));
+        weather_station.push(temperature("Hall", 19.5, Some(150)));
+        weather_station.push(temperature("Lab", 20.5, Some(150)));
+        weather_station.push((
+            RadiationSensorKind::Gamma,
+            RadiationSensor {
+                metadata: SensorMetadata::default(),
+                dead_time: None,
+                conversion_factor: None,
+                unit: RadiationSensorUnit::CountsPerMinute,
+                value: 12.0,
+            },
+        ));
+        (door_controller, weather_station)
+    }
+
+    #[test]
+    fn merge_newest_wins() {
+        let (mut sensors, other) = collections();
+        sensors.merge(other, MergeStrategy::NewestWins).unwrap();
+        assert_eq!(
+            sensors.temperature,
+            [
+                temperature("Hall", 19.5, Some(150)),
+                temperature("Lab", 21.0, Some(200)),
+                temperature("Roof", 8.0, Some(150)),
+            ]
+        );
+        assert_eq!(sensors.door_locked.len(), 1);
+        assert_eq!(
+            sensors.radiation.as_ref().unwrap().gamma.as_ref().unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn merge_is_deterministic() {
+        let (mut a, b) = collections();
+        a.push(temperature("Roof", 7.5, Some(150)));
+        let mut ab = a.clone();
+        ab.merge(b.clone(), MergeStrategy::KeepBoth).unwrap();
+        let mut ba = b;
+        ba.merge(a, MergeStrategy::KeepBoth).unwrap();
+        assert_eq!(
+            ab.temperature,
+            [
+                temperature("Hall", 19.0, Some(100)),
+                temperature("Hall", 19.5, Some(150)),
+                temperature("Lab", 20.5, Some(150)),
+                temperature("Lab", 21.0, Some(200)),
+                temperature("Roof", 7.5, Some(150)),
+                temperature("Roof", 8.0, Some(150)),
+            ]
+        );
+        assert_eq!(ab, ba);
+    }
+
+    #[test]
+    fn merge_conflict() {
+        let (mut sensors, other) = collections();
+        let before = sensors.clone();
+        let error = sensors.merge(other, MergeStrategy::Error).unwrap_err();
+        assert_eq!(
+            error,
+            MergeConflict::Sensor {
+                kind: SensorKind::Temperature,
+                key: SensorKey::new(None, Some("Hall")),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "temperature sensor at \"Hall\" is present more than once"
+        );
+        assert_eq!(sensors, before);
+    }
+
+    #[test]
+    fn merge_conflict_within_other() {
+        let mut sensors = Sensors::default();
+        let mut other = Sensors::default();
+        other.push(temperature("Hall", 19.0, Some(100)));
+        other.push(temperature("Hall", 19.5, Some(150)));
+        assert_eq!(
+            sensors.merge(other, MergeStrategy::Error),
+            Err(MergeConflict::Sensor {
+                kind: SensorKind::Temperature,
+                key: SensorKey::new(None, Some("Hall")),
+            })
+        );
+        assert!(sensors.is_empty());
+    }
+
+    #[test]
+    fn merge_unknown_kinds() {
+        let json = r#"{"noise":[{"value":40}],"light":{"lux":3}}"#;
+        let other_json = r#"{"noise":[{"value":45}],"light":{"lux":4},"sound":[]}"#;
+        for strategy in [MergeStrategy::NewestWins, MergeStrategy::KeepBoth] {
+            let mut sensors: Sensors = serde_json::from_str(json).unwrap();
+            sensors
+                .merge(serde_json::from_str(other_json).unwrap(), strategy)
+                .unwrap();
+            assert_eq!(
+                serde_json::to_string(&sensors).unwrap(),
+                r#"{"light":{"lux":4},"noise":[{"value":40},{"value":45}],"sound":[]}"#
+            );
+        }
+
+        let mut sensors: Sensors = serde_json::from_str(json).unwrap();
+        let before = sensors.clone();
+        let other: Sensors = serde_json::from_str(other_json).unwrap();
+        let error = sensors.merge(other, MergeStrategy::Error).unwrap_err();
+        assert_eq!(error, MergeConflict::UnknownKind("light".into()));
+        assert_eq!(
+            error.to_string(),
+            "unknown sensor kind \"light\" is present in both collections"
+        );
+        assert_eq!(sensors, before);
+    }
+}
@@ -1,45 +1,79 @@
 //! Module defining common sensor functionality.
 
 mod account_balance;
+mod any_sensor;
 mod barometer;
 mod beverage_supply;
+mod carbondioxide;
+mod config;
 mod door_locked;
+mod gas_concentration;
 mod humidity;
+mod kind;
+mod merge;
 mod network_connections;
 mod network_traffic;
+mod particulate_matter;
 mod people_now_present;
 mod power_consumption;
+mod power_generation;
 mod radiation;
+mod sensor;
 mod temperature;
 mod total_member_count;
+mod units;
+mod update;
+mod value;
 mod wind;
 
 pub use account_balance::{AccountBalanceSensor, AccountBalanceSensorTemplate};
+pub use any_sensor::AnySensor;
 pub use barometer::{BarometerSensor, BarometerSensorTemplate};
 pub use beverage_supply::{BeverageSupplySensor, BeverageSupplySensorTemplate};
+pub use carbondioxide::{CarbonDioxideSensor, CarbonDioxideSensorTemplate};
+pub use config::{SensorConfig, SensorConfigError, SensorRegistry, SensorRegistryError};
 pub use door_locked::{DoorLockedSensor, DoorLockedSensorTemplate};
+pub use gas_concentration::{GasConcentrationSensor, GasConcentrationSensorTemplate};
 pub use humidity::{HumiditySensor, HumiditySensorTemplate};
+pub use kind::SensorKind;
+pub use merge::{MergeConflict, MergeStrategy};
 pub use network_connections::{
     NetworkConnectionKind, NetworkConnectionMachine, NetworkConnectionsSensor,
     NetworkConnectionsSensorTemplate,
 };
 pub use network_traffic::{
     NetworkTrafficBitsPerSecond, NetworkTrafficPacketsPerSecond, NetworkTrafficSensor,
-    NetworkTrafficSensorProperties,
+    NetworkTrafficSensorProperties, NetworkTrafficSensorTemplate,
 };
+pub use particulate_matter::{ParticleSize, ParticulateMatterSensor, ParticulateMatterSensorTemplate};
 pub use people_now_present::{PeopleNowPresentSensor, PeopleNowPresentSensorTemplate};
 pub use power_consumption::{PowerConsumptionSensor, PowerConsumptionSensorTemplate};
-pub use radiation::{RadiationSensor, RadiationSensorUnit, RadiationSensors};
+pub use power_generation::{PowerGenerationSensor, PowerGenerationSensorTemplate};
+pub use radiation::{
+    RadiationSensor, RadiationSensorKind, RadiationSensorTemplate, RadiationSensorUnit, RadiationSensors,
+};
+pub use sensor::Sensor;
 pub use temperature::{TemperatureSensor, TemperatureSensorTemplate};
 pub use total_member_count::{TotalMemberCountSensor, TotalMemberCountSensorTemplate};
-pub use wind::{WindSensor, WindSensorMeasurement, WindSensorProperties};
+pub use units::{
+    BarometerUnit, CarbonDioxideUnit, HumidityUnit, PowerUnit, TemperatureUnit, UnitConversionError, WindUnit,
+};
+pub use update::SensorKey;
+pub use value::SensorValue;
+pub use wind::{WindSensor, WindSensorMeasurement, WindSensorProperties, WindSensorTemplate};
 
+use crate::clock::{Clock, SystemClock};
+use crate::status::{BuildError, SpecVersion};
 use log::warn;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
 use thiserror::Error;
 
 /// Common information describing any sensor.
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SensorMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -47,21 +81,28 @@ pub struct SensorMetadata {
     pub location: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Unix timestamp of the last measurement, introduced in v15
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastchange: Option<u64>,
 }
 
 /// Common information describing any sensor which requires a specified location.
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SensorMetadataWithLocation {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     pub location: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Unix timestamp of the last measurement, introduced in v15
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastchange: Option<u64>,
 }
 
-/// Describes an error occurring when building a sensor from a `SensorTemplate`.
+/// Describes why a value was rejected when building a sensor from a `SensorTemplate`.
 #[derive(Error, Debug)]
-pub enum SensorTemplateError {
+pub enum SensorValueError {
     /// Failed when parsing an integer value from the provided value string
     #[error("sensor integer value cannot be parsed")]
     BadInteger(#[from] std::num::ParseIntError),
@@ -73,11 +114,154 @@ pub enum SensorTemplateError {
     /// Failed when parsing a boolean value from the provided value string
     #[error("sensor boolean value cannot be parsed")]
     BadBool(#[from] std::str::ParseBoolError),
+
+    /// The provided value string is not a comma separated list of `name=value` pairs
+    #[error("sensor value {0:?} is not a list of name=value pairs")]
+    MalformedNamedValues(String),
+
+    /// A named value required by the sensor is missing
+    #[error("sensor value {0} is missing")]
+    MissingNamedValue(&'static str),
+
+    /// A named value is not known to the sensor
+    #[error("sensor value {0} is unknown")]
+    UnknownNamedValue(String),
+
+    /// The provided value is of a kind the sensor cannot be created from
+    #[error("sensor value must be {expected}, found {found}")]
+    UnexpectedValue {
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    /// Failed when decoding the provided JSON value
+    #[error("sensor JSON value cannot be decoded")]
+    BadJson(#[from] serde_json::Error),
+
+    /// The provided value lies outside of the range that is valid for the sensor
+    #[error("sensor value {value} must be {constraint}")]
+    OutOfRange { value: f64, constraint: &'static str },
+}
+
+/// Describes an error occurring when building a sensor from a `SensorTemplate`.
+#[derive(Error, Debug)]
+pub struct SensorTemplateError {
+    /// The kind of sensor that was built
+    pub kind: SensorKind,
+    /// The name of the sensor, if any
+    pub name: Option<String>,
+    /// The location of the sensor, if any
+    pub location: Option<String>,
+    /// The rejected input
+    pub input: String,
+    /// The reason the input was rejected
+    pub error: SensorValueError,
+}
+
+impl fmt::Display for SensorTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} sensor", self.kind)?;
+        if let Some(name) = &self.name {
+            write!(f, " {:?}", name)?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, " at {:?}", location)?;
+        }
+        write!(f, " rejected input {:?}: {}", self.input, self.error)
+    }
+}
+
+/// Uniform access to both kinds of sensor metadata.
+pub(crate) trait Metadata {
+    fn name(&self) -> Option<&str>;
+    fn location(&self) -> Option<&str>;
+    fn description(&self) -> Option<&str>;
+    fn lastchange(&self) -> Option<u64>;
+    fn set_lastchange(&mut self, lastchange: Option<u64>);
 }
 
-/// Trait that allows sensors to be created from a template and string value.
+impl Metadata for SensorMetadata {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    fn lastchange(&self) -> Option<u64> {
+        self.lastchange
+    }
+
+    fn set_lastchange(&mut self, lastchange: Option<u64>) {
+        self.lastchange = lastchange;
+    }
+}
+
+impl Metadata for SensorMetadataWithLocation {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn location(&self) -> Option<&str> {
+        Some(&self.location)
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    fn lastchange(&self) -> Option<u64> {
+        self.lastchange
+    }
+
+    fn set_lastchange(&mut self, lastchange: Option<u64>) {
+        self.lastchange = lastchange;
+    }
+}
+
+/// Run `build`, adding the sensor and its input to the error if it fails.
+///
+/// The built sensor's `lastchange` is set to the time of `clock`.
+fn with_context<T: Sensor>(
+    kind: SensorKind,
+    metadata: &impl Metadata,
+    value: &SensorValue,
+    clock: &dyn Clock,
+    build: impl FnOnce() -> Result<T, SensorValueError>,
+) -> Result<T, SensorTemplateError> {
+    let mut sensor = build().map_err(|error| SensorTemplateError {
+        kind,
+        name: metadata.name().map(str::to_owned),
+        location: metadata.location().map(str::to_owned),
+        input: value.to_string(),
+        error,
+    })?;
+    sensor.set_lastchange(Some(clock.timestamp()));
+    Ok(sensor)
+}
+
+/// Trait that allows sensors to be created from a template and a value.
+///
+/// The sensor's `lastchange` is set to the current time, or to the time of the
+/// given `Clock` with `try_from_template_with`.
 pub trait FromSensorTemplate<T> {
-    fn try_from_template(template: &T, value: &str) -> Result<Self, SensorTemplateError>
+    fn try_from_template(template: &T, value: &SensorValue) -> Result<Self, SensorTemplateError>
+    where
+        Self: Sized,
+    {
+        Self::try_from_template_with(template, value, &SystemClock)
+    }
+
+    fn try_from_template_with(
+        template: &T,
+        value: &SensorValue,
+        clock: &dyn Clock,
+    ) -> Result<Self, SensorTemplateError>
     where
         Self: Sized;
 }
@@ -86,18 +270,51 @@ pub trait FromSensorTemplate<T> {
 ///
 /// A sensor template is like a sensor struct, but without the actual data in it.
 /// A `SensorTemplate` is capable of registering itself in a `Sensors` struct.
+///
+/// Registered sensors have their `lastchange` set to the current time. The
+/// `_with` variants take the time from the given `Clock` instead.
 pub trait SensorTemplate: Send + Sync {
     fn to_sensor(&self, value_str: &str, sensors: &mut Sensors) {
-        if let Err(e) = self.try_to_sensor(value_str, sensors) {
+        self.to_sensor_with(value_str, sensors, &SystemClock);
+    }
+
+    fn to_sensor_with(&self, value_str: &str, sensors: &mut Sensors, clock: &dyn Clock) {
+        self.to_sensor_value_with(&SensorValue::from(value_str), sensors, clock);
+    }
+
+    fn to_sensor_value(&self, value: &SensorValue, sensors: &mut Sensors) {
+        self.to_sensor_value_with(value, sensors, &SystemClock);
+    }
+
+    fn to_sensor_value_with(&self, value: &SensorValue, sensors: &mut Sensors, clock: &dyn Clock) {
+        if let Err(e) = self.try_to_sensor_value_with(value, sensors, clock) {
             warn!("Omitting sensor. Reason: {}", e);
         }
     }
 
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError>;
+    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+        self.try_to_sensor_value(&SensorValue::from(value_str), sensors)
+    }
+
+    fn try_to_sensor_value(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+    ) -> Result<(), SensorTemplateError> {
+        self.try_to_sensor_value_with(value, sensors, &SystemClock)
+    }
+
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError>;
 }
 
 /// Container for instances of all sensor types.
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Sensors {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub temperature: Vec<TemperatureSensor>,
@@ -125,6 +342,117 @@ pub struct Sensors {
     pub people_now_present: Vec<PeopleNowPresentSensor>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub network_traffic: Vec<NetworkTrafficSensor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub carbondioxide: Vec<CarbonDioxideSensor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub power_generation: Vec<PowerGenerationSensor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub particulate_matter: Vec<ParticulateMatterSensor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gas_concentration: Vec<GasConcentrationSensor>,
+
+    /// Sensor kinds unknown to this crate, kept as they are.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+/// The dotted path of the `lastchange` field of sensors of the given kind.
+pub(crate) fn lastchange_field(kind: SensorKind) -> &'static str {
+    match kind {
+        SensorKind::Temperature => "sensors.temperature.lastchange",
+        SensorKind::DoorLocked => "sensors.door_locked.lastchange",
+        SensorKind::Barometer => "sensors.barometer.lastchange",
+        SensorKind::Radiation(RadiationSensorKind::Alpha) => "sensors.radiation.alpha.lastchange",
+        SensorKind::Radiation(RadiationSensorKind::Beta) => "sensors.radiation.beta.lastchange",
+        SensorKind::Radiation(RadiationSensorKind::Gamma) => "sensors.radiation.gamma.lastchange",
+        SensorKind::Radiation(RadiationSensorKind::BetaGamma) => "sensors.radiation.beta_gamma.lastchange",
+        SensorKind::Humidity => "sensors.humidity.lastchange",
+        SensorKind::BeverageSupply => "sensors.beverage_supply.lastchange",
+        SensorKind::PowerConsumption => "sensors.power_consumption.lastchange",
+        SensorKind::Wind => "sensors.wind.lastchange",
+        SensorKind::NetworkConnections => "sensors.network_connections.lastchange",
+        SensorKind::AccountBalance => "sensors.account_balance.lastchange",
+        SensorKind::TotalMemberCount => "sensors.total_member_count.lastchange",
+        SensorKind::PeopleNowPresent => "sensors.people_now_present.lastchange",
+        SensorKind::NetworkTraffic => "sensors.network_traffic.lastchange",
+        SensorKind::CarbonDioxide => "sensors.carbondioxide.lastchange",
+        SensorKind::PowerGeneration => "sensors.power_generation.lastchange",
+        SensorKind::ParticulateMatter => "sensors.particulate_matter.lastchange",
+        SensorKind::GasConcentration => "sensors.gas_concentration.lastchange",
+    }
+}
+
+impl Sensors {
+    /// Remove the `lastchange` of all sensors, returning the kinds of the
+    /// sensors that had one.
+    pub(crate) fn remove_lastchange(&mut self) -> Vec<SensorKind> {
+        let mut kinds = vec![];
+        for (kind, sensor) in self.iter_mut() {
+            if sensor.lastchange().is_some() {
+                sensor.set_lastchange(None);
+                if kinds.last() != Some(&kind) {
+                    kinds.push(kind);
+                }
+            }
+        }
+        kinds
+    }
+
+    /// Check that only sensor kinds and fields known to the given version are present.
+    ///
+    /// A `lastchange` before v15 is reported once per sensor kind.
+    pub(crate) fn verify(&self, version: SpecVersion, errors: &mut Vec<BuildError>) {
+        let kinds = [
+            (
+                "sensors.carbondioxide",
+                self.carbondioxide.is_empty(),
+                SpecVersion::V14,
+            ),
+            (
+                "sensors.power_generation",
+                self.power_generation.is_empty(),
+                SpecVersion::V14,
+            ),
+            (
+                "sensors.particulate_matter",
+                self.particulate_matter.is_empty(),
+                SpecVersion::V15,
+            ),
+            (
+                "sensors.gas_concentration",
+                self.gas_concentration.is_empty(),
+                SpecVersion::V15,
+            ),
+        ];
+        for (field, empty, since) in kinds {
+            if !empty && version < since {
+                errors.push(BuildError::UnavailableField {
+                    field,
+                    version: since,
+                });
+            }
+        }
+        if version < SpecVersion::V15 {
+            let mut kinds: Vec<_> = self
+                .iter()
+                .filter(|(_, sensor)| sensor.lastchange().is_some())
+                .map(|(kind, _)| kind)
+                .collect();
+            kinds.dedup();
+            for kind in kinds {
+                errors.push(BuildError::UnavailableField {
+                    field: lastchange_field(kind),
+                    version: SpecVersion::V15,
+                });
+            }
+        }
+    }
+}
+
+/// A fixed clock for tests of sensors built from templates.
+#[cfg(test)]
+pub(crate) fn test_clock() -> std::time::SystemTime {
+    std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000)
 }
 
 #[cfg(test)]
@@ -138,4 +466,65 @@ mod test {
         let b: Sensors = from_str(&to_string(&a).unwrap()).unwrap();
         assert_eq!(a, b);
     }
+    #[test]
+    fn serialize_deserialize_v15_sensors() {
+        let json = "{\"temperature\":[{\"location\":\"Hall\",\"lastchange\":1700000000,\"unit\":\"°C\",\"value\":21.5}],\
+                    \"gas_concentration\":[{\"location\":\"Lab\",\"gas\":\"CO\",\"unit\":\"ppm\",\"value\":3.0}]}";
+        let sensors: Sensors = from_str(json).unwrap();
+        assert_eq!(sensors.temperature[0].metadata.lastchange, Some(1_700_000_000));
+        assert_eq!(sensors.gas_concentration[0].gas, "CO");
+        assert_eq!(to_string(&sensors).unwrap(), json);
+
+        let mut errors = vec![];
+        sensors.verify(SpecVersion::V14, &mut errors);
+        assert_eq!(
+            errors,
+            [
+                BuildError::UnavailableField {
+                    field: "sensors.gas_concentration",
+                    version: SpecVersion::V15,
+                },
+                BuildError::UnavailableField {
+                    field: "sensors.temperature.lastchange",
+                    version: SpecVersion::V15,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn serialize_deserialize_v14_sensors() {
+        let json = "{\"carbondioxide\":[{\"location\":\"Hall\",\"unit\":\"ppm\",\"value\":600.0}],\
+                    \"power_generation\":[{\"location\":\"Roof\",\"unit\":\"W\",\"value\":12.0}]}";
+        let sensors: Sensors = from_str(json).unwrap();
+        assert_eq!(to_string(&sensors).unwrap(), json);
+
+        let mut errors = vec![];
+        sensors.verify(SpecVersion::V14, &mut errors);
+        assert_eq!(errors, []);
+        sensors.verify(SpecVersion::V0_13, &mut errors);
+        assert_eq!(
+            errors,
+            [
+                BuildError::UnavailableField {
+                    field: "sensors.carbondioxide",
+                    version: SpecVersion::V14,
+                },
+                BuildError::UnavailableField {
+                    field: "sensors.power_generation",
+                    version: SpecVersion::V14,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn serialize_deserialize_unknown_kinds() {
+        let json = "{\"temperature\":[{\"location\":\"Hall\",\"unit\":\"°C\",\"value\":21.5}],\
+                    \"noise\":[{\"location\":\"Hall\",\"unit\":\"dB\",\"value\":40}]}";
+        let sensors: Sensors = from_str(json).unwrap();
+        assert_eq!(sensors.len(), 1);
+        assert_eq!(sensors.other["noise"][0]["unit"], "dB");
+        assert_eq!(to_string(&sensors).unwrap(), json);
+    }
 }
@@ -1,9 +1,14 @@
 //! Module providing network connections sensor functionality.
 
-use super::{FromSensorTemplate, SensorMetadata, SensorTemplate, SensorTemplateError, Sensors};
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadata, SensorTemplate, SensorTemplateError,
+    SensorValue, Sensors,
+};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NetworkConnectionsSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadata,
@@ -15,6 +20,7 @@ pub struct NetworkConnectionsSensor {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum NetworkConnectionKind {
     Wifi,
@@ -23,37 +29,104 @@ pub enum NetworkConnectionKind {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NetworkConnectionMachine {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     pub mac: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct NetworkConnectionsSensorTemplate {
+    #[serde(flatten)]
     pub metadata: SensorMetadata,
+    #[serde(rename = "type")]
     pub kind: Option<NetworkConnectionKind>,
 }
 
 impl FromSensorTemplate<NetworkConnectionsSensorTemplate> for NetworkConnectionsSensor {
-    fn try_from_template(
+    fn try_from_template_with(
         template: &NetworkConnectionsSensorTemplate,
-        value: &str,
+        value: &SensorValue,
+        clock: &dyn Clock,
     ) -> Result<Self, SensorTemplateError> {
-        Ok(Self {
-            metadata: template.metadata.clone(),
-            kind: template.kind.clone(),
-            value: value.parse()?,
-            ..Default::default()
-        })
+        with_context(
+            SensorKind::NetworkConnections,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                // Machines are given as a list of MAC addresses or as JSON objects
+                let machines: Option<Vec<NetworkConnectionMachine>> = match (value.as_list(), value) {
+                    (Some(macs), _) => Some(
+                        macs.into_iter()
+                            .map(|mac| NetworkConnectionMachine { name: None, mac })
+                            .collect(),
+                    ),
+                    (None, SensorValue::Json(json @ Value::Array(_))) => {
+                        Some(serde_json::from_value(json.clone())?)
+                    }
+                    (None, _) => None,
+                };
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    kind: template.kind.clone(),
+                    value: match &machines {
+                        Some(machines) => machines.len() as u64,
+                        None => value.parse_count()?,
+                    },
+                    machines,
+                })
+            },
+        )
     }
 }
 
 impl SensorTemplate for NetworkConnectionsSensorTemplate {
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
         sensors
             .network_connections
-            .push(NetworkConnectionsSensor::try_from_template(self, value_str)?);
+            .push(NetworkConnectionsSensor::try_from_template_with(
+                self, value, clock,
+            )?);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::test_clock;
+    use serde_json::json;
+
+    #[test]
+    fn test_template() {
+        let template = NetworkConnectionsSensorTemplate {
+            metadata: SensorMetadata::default(),
+            kind: Some(NetworkConnectionKind::Wifi),
+        };
+
+        let mut sensors = Sensors::default();
+        template.to_sensor_with("3", &mut sensors, &test_clock());
+        template.to_sensor_value_with(
+            &json!([{"name": "laptop", "mac": "00:11:22:33:44:55"}]).into(),
+            &mut sensors,
+            &test_clock(),
+        );
+        template.to_sensor_value_with(
+            &json!({"mac": "00:11:22:33:44:55"}).into(),
+            &mut sensors,
+            &test_clock(),
+        );
+
+        assert_eq!(
+            "[{\"lastchange\":1700000000,\"type\":\"wifi\",\"value\":3},{\"lastchange\":1700000000,\"machines\":[{\"name\":\"laptop\",\"mac\":\"00:11:22:33:44:55\"}],\"type\":\"wifi\",\"value\":1}]",
+            serde_json::to_string(&sensors.network_connections).unwrap()
+        );
+    }
+}
@@ -1,9 +1,13 @@
 //! Module providing network traffic sensor functionality.
 
-use super::SensorMetadata;
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadata, SensorTemplate, SensorTemplateError,
+    SensorValue, SensorValueError, Sensors,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NetworkTrafficSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadata,
@@ -11,6 +15,7 @@ pub struct NetworkTrafficSensor {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NetworkTrafficSensorProperties {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bits_per_second: Option<NetworkTrafficBitsPerSecond>,
@@ -19,6 +24,7 @@ pub struct NetworkTrafficSensorProperties {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NetworkTrafficBitsPerSecond {
     pub value: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -26,13 +32,76 @@ pub struct NetworkTrafficBitsPerSecond {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NetworkTrafficPacketsPerSecond {
     pub value: f64,
 }
 
+/// Template for a `NetworkTrafficSensor`.
+///
+/// The value string consists of the named values `bits_per_second` and
+/// `packets_per_second`, of which at least one must be present, e.g.
+/// `bits_per_second=1200000,packets_per_second=340`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetworkTrafficSensorTemplate {
+    #[serde(flatten)]
+    pub metadata: SensorMetadata,
+    /// The maximum available bandwidth in bits per second
+    pub maximum_bits_per_second: Option<f64>,
+}
+
+impl FromSensorTemplate<NetworkTrafficSensorTemplate> for NetworkTrafficSensor {
+    fn try_from_template_with(
+        template: &NetworkTrafficSensorTemplate,
+        value: &SensorValue,
+        clock: &dyn Clock,
+    ) -> Result<Self, SensorTemplateError> {
+        with_context(
+            SensorKind::NetworkTraffic,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                let values = value.named_values(&["bits_per_second", "packets_per_second"])?;
+                let bits_per_second = values.get("bits_per_second");
+                let packets_per_second = values.get("packets_per_second");
+                if bits_per_second.is_none() && packets_per_second.is_none() {
+                    return Err(SensorValueError::MissingNamedValue("bits_per_second"));
+                }
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    properties: NetworkTrafficSensorProperties {
+                        bits_per_second: bits_per_second.map(|value| NetworkTrafficBitsPerSecond {
+                            value,
+                            maximum: template.maximum_bits_per_second,
+                        }),
+                        packets_per_second: packets_per_second
+                            .map(|value| NetworkTrafficPacketsPerSecond { value }),
+                    },
+                })
+            },
+        )
+    }
+}
+
+impl SensorTemplate for NetworkTrafficSensorTemplate {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
+        sensors
+            .network_traffic
+            .push(NetworkTrafficSensor::try_from_template_with(self, value, clock)?);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::sensors::test_clock;
 
     #[test]
     fn test_serialize() {
@@ -52,4 +121,26 @@ mod test {
             serde_json::to_string(&sensor).unwrap()
         );
     }
+
+    #[test]
+    fn test_template() {
+        let template = NetworkTrafficSensorTemplate {
+            metadata: SensorMetadata::default(),
+            maximum_bits_per_second: Some(1e8),
+        };
+
+        let mut sensors = Sensors::default();
+        template.to_sensor_with(
+            "bits_per_second=1200000,packets_per_second=340",
+            &mut sensors,
+            &test_clock(),
+        );
+        template.to_sensor_with("packets_per_second=12", &mut sensors, &test_clock());
+        template.to_sensor_with("", &mut sensors, &test_clock());
+
+        assert_eq!(
+            "[{\"lastchange\":1700000000,\"properties\":{\"bits_per_second\":{\"value\":1200000.0,\"maximum\":100000000.0},\"packets_per_second\":{\"value\":340.0}}},{\"lastchange\":1700000000,\"properties\":{\"packets_per_second\":{\"value\":12.0}}}]",
+            serde_json::to_string(&sensors.network_traffic).unwrap()
+        );
+    }
 }
@@ -0,0 +1,103 @@
+//! Module providing particulate matter sensor functionality.
+
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadataWithLocation, SensorTemplate,
+    SensorTemplateError, SensorValue, Sensors,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ParticulateMatterSensor {
+    #[serde(flatten)]
+    pub metadata: SensorMetadataWithLocation,
+    pub size: ParticleSize,
+    pub unit: String,
+    pub value: f64,
+}
+
+/// The particle size class a particulate matter sensor measures.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ParticleSize {
+    #[serde(rename = "PM1")]
+    Pm1,
+    #[default]
+    #[serde(rename = "PM2.5")]
+    Pm2_5,
+    #[serde(rename = "PM10")]
+    Pm10,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParticulateMatterSensorTemplate {
+    #[serde(flatten)]
+    pub metadata: SensorMetadataWithLocation,
+    pub size: ParticleSize,
+    pub unit: String,
+}
+
+impl FromSensorTemplate<ParticulateMatterSensorTemplate> for ParticulateMatterSensor {
+    fn try_from_template_with(
+        template: &ParticulateMatterSensorTemplate,
+        value: &SensorValue,
+        clock: &dyn Clock,
+    ) -> Result<Self, SensorTemplateError> {
+        with_context(
+            SensorKind::ParticulateMatter,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    size: template.size.clone(),
+                    unit: template.unit.clone(),
+                    value: value.parse()?,
+                })
+            },
+        )
+    }
+}
+
+impl SensorTemplate for ParticulateMatterSensorTemplate {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
+        sensors
+            .particulate_matter
+            .push(ParticulateMatterSensor::try_from_template_with(
+                self, value, clock,
+            )?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::test_clock;
+
+    #[test]
+    fn test_template() {
+        let template = ParticulateMatterSensorTemplate {
+            metadata: SensorMetadataWithLocation {
+                location: "Workshop".into(),
+                ..Default::default()
+            },
+            size: ParticleSize::Pm10,
+            unit: "µg/m³".into(),
+        };
+
+        let mut sensors = Sensors::default();
+        template.to_sensor_with("12.5", &mut sensors, &test_clock());
+
+        assert_eq!(
+            "[{\"location\":\"Workshop\",\"lastchange\":1700000000,\"size\":\"PM10\",\"unit\":\"µg/m³\",\"value\":12.5}]",
+            serde_json::to_string(&sensors.particulate_matter).unwrap()
+        );
+    }
+}
@@ -1,9 +1,13 @@
 //! Module providing people present sensor functionality.
 
-use super::{FromSensorTemplate, SensorMetadata, SensorTemplate, SensorTemplateError, Sensors};
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadata, SensorTemplate, SensorTemplateError,
+    SensorValue, Sensors,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PeopleNowPresentSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadata,
@@ -12,29 +16,54 @@ pub struct PeopleNowPresentSensor {
     pub value: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct PeopleNowPresentSensorTemplate {
+    #[serde(flatten)]
     pub metadata: SensorMetadata,
 }
 
 impl FromSensorTemplate<PeopleNowPresentSensorTemplate> for PeopleNowPresentSensor {
-    fn try_from_template(
+    fn try_from_template_with(
         template: &PeopleNowPresentSensorTemplate,
-        value: &str,
+        value: &SensorValue,
+        clock: &dyn Clock,
     ) -> Result<Self, SensorTemplateError> {
-        Ok(Self {
-            metadata: template.metadata.clone(),
-            value: value.parse()?,
-            ..Default::default()
-        })
+        with_context(
+            SensorKind::PeopleNowPresent,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                // A list of names also determines the number of people present
+                if let Some(names) = value.as_list() {
+                    return Ok(Self {
+                        metadata: template.metadata.clone(),
+                        value: names.len() as u64,
+                        names: Some(names),
+                    });
+                }
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    value: value.parse_count()?,
+                    ..Default::default()
+                })
+            },
+        )
     }
 }
 
 impl SensorTemplate for PeopleNowPresentSensorTemplate {
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
         sensors
             .people_now_present
-            .push(PeopleNowPresentSensor::try_from_template(self, value_str)?);
+            .push(PeopleNowPresentSensor::try_from_template_with(
+                self, value, clock,
+            )?);
         Ok(())
     }
 }
@@ -42,6 +71,7 @@ impl SensorTemplate for PeopleNowPresentSensorTemplate {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::sensors::test_clock;
 
     #[test]
     fn test_template() {
@@ -50,10 +80,30 @@ mod test {
         };
 
         let mut sensors = Sensors::default();
-        template.to_sensor("8", &mut sensors);
+        template.to_sensor_with("8", &mut sensors, &test_clock());
+
+        assert_eq!(
+            "[{\"lastchange\":1700000000,\"value\":8}]",
+            serde_json::to_string(&sensors.people_now_present).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_template_names() {
+        let template = PeopleNowPresentSensorTemplate {
+            metadata: SensorMetadata::default(),
+        };
+
+        let mut sensors = Sensors::default();
+        template.to_sensor_value_with(
+            &vec!["Alice".to_owned(), "Bob".to_owned()].into(),
+            &mut sensors,
+            &test_clock(),
+        );
+        template.to_sensor_value_with(&serde_json::json!(["Carol"]).into(), &mut sensors, &test_clock());
 
         assert_eq!(
-            "[{\"value\":8}]",
+            "[{\"lastchange\":1700000000,\"names\":[\"Alice\",\"Bob\"],\"value\":2},{\"lastchange\":1700000000,\"names\":[\"Carol\"],\"value\":1}]",
             serde_json::to_string(&sensors.people_now_present).unwrap()
         );
     }
@@ -69,8 +119,29 @@ mod test {
 
         assert!(result.is_err());
         assert_eq!(
-            "sensor integer value cannot be parsed",
+            "people_now_present sensor rejected input \"two\": sensor integer value cannot be parsed",
             result.err().unwrap().to_string()
         );
     }
+
+    #[test]
+    fn test_template_negative_count() {
+        let template = PeopleNowPresentSensorTemplate {
+            metadata: SensorMetadata {
+                name: Some("Lab".into()),
+                ..SensorMetadata::default()
+            },
+        };
+
+        let mut sensors = Sensors::default();
+        let error = template.try_to_sensor("-2", &mut sensors).err().unwrap();
+
+        assert_eq!(error.kind, SensorKind::PeopleNowPresent);
+        assert_eq!(error.input, "-2");
+        assert_eq!(
+            "people_now_present sensor \"Lab\" rejected input \"-2\": sensor value -2 must be at least 0",
+            error.to_string()
+        );
+        assert!(sensors.people_now_present.is_empty());
+    }
 }
@@ -1,40 +1,69 @@
 //! Module providing power consumption sensor functionality.
 
-use super::{FromSensorTemplate, SensorMetadataWithLocation, SensorTemplate, SensorTemplateError, Sensors};
+use super::{
+    with_context, Clock, FromSensorTemplate, PowerUnit, SensorKind, SensorMetadataWithLocation,
+    SensorTemplate, SensorTemplateError, SensorValue, Sensors, UnitConversionError,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PowerConsumptionSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadataWithLocation,
-    pub unit: String,
+    pub unit: PowerUnit,
     pub value: f64,
 }
 
-#[derive(Debug, Clone)]
+impl PowerConsumptionSensor {
+    /// Return the value of this sensor converted to the given unit.
+    pub fn value_in(&self, unit: PowerUnit) -> Result<f64, UnitConversionError> {
+        self.unit.convert(self.value, &unit)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct PowerConsumptionSensorTemplate {
+    #[serde(flatten)]
     pub metadata: SensorMetadataWithLocation,
-    pub unit: String,
+    #[serde(default)]
+    pub unit: PowerUnit,
 }
 
 impl FromSensorTemplate<PowerConsumptionSensorTemplate> for PowerConsumptionSensor {
-    fn try_from_template(
+    fn try_from_template_with(
         template: &PowerConsumptionSensorTemplate,
-        value: &str,
+        value: &SensorValue,
+        clock: &dyn Clock,
     ) -> Result<Self, SensorTemplateError> {
-        Ok(Self {
-            metadata: template.metadata.clone(),
-            unit: template.unit.clone(),
-            value: value.parse()?,
-        })
+        with_context(
+            SensorKind::PowerConsumption,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    unit: template.unit.clone(),
+                    value: value.parse()?,
+                })
+            },
+        )
     }
 }
 
 impl SensorTemplate for PowerConsumptionSensorTemplate {
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
         sensors
             .power_consumption
-            .push(PowerConsumptionSensor::try_from_template(self, value_str)?);
+            .push(PowerConsumptionSensor::try_from_template_with(
+                self, value, clock,
+            )?);
         Ok(())
     }
 }
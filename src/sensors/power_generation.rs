@@ -0,0 +1,102 @@
+//! Module providing power generation sensor functionality.
+
+use super::{
+    with_context, Clock, FromSensorTemplate, PowerUnit, SensorKind, SensorMetadataWithLocation,
+    SensorTemplate, SensorTemplateError, SensorValue, Sensors, UnitConversionError,
+};
+use serde::{Deserialize, Serialize};
+
+/// A power generation sensor, introduced in v14.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PowerGenerationSensor {
+    #[serde(flatten)]
+    pub metadata: SensorMetadataWithLocation,
+    pub unit: PowerUnit,
+    pub value: f64,
+}
+
+impl PowerGenerationSensor {
+    /// Return the value of this sensor converted to the given unit.
+    pub fn value_in(&self, unit: PowerUnit) -> Result<f64, UnitConversionError> {
+        self.unit.convert(self.value, &unit)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PowerGenerationSensorTemplate {
+    #[serde(flatten)]
+    pub metadata: SensorMetadataWithLocation,
+    #[serde(default)]
+    pub unit: PowerUnit,
+}
+
+impl FromSensorTemplate<PowerGenerationSensorTemplate> for PowerGenerationSensor {
+    fn try_from_template_with(
+        template: &PowerGenerationSensorTemplate,
+        value: &SensorValue,
+        clock: &dyn Clock,
+    ) -> Result<Self, SensorTemplateError> {
+        with_context(
+            SensorKind::PowerGeneration,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    unit: template.unit.clone(),
+                    value: value.parse()?,
+                })
+            },
+        )
+    }
+}
+
+impl SensorTemplate for PowerGenerationSensorTemplate {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
+        sensors
+            .power_generation
+            .push(PowerGenerationSensor::try_from_template_with(self, value, clock)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::test_clock;
+    use serde_json::{from_str, to_string};
+
+    #[test]
+    fn serialize_deserialize() {
+        let json =
+            "[{\"location\":\"Roof\",\"description\":\"Solar panels\",\"unit\":\"mW\",\"value\":1500.0}]";
+        let sensors: Vec<PowerGenerationSensor> = from_str(json).unwrap();
+        assert_eq!(sensors[0].value_in(PowerUnit::Watt).unwrap(), 1.5);
+        assert_eq!(to_string(&sensors).unwrap(), json);
+    }
+
+    #[test]
+    fn test_template() {
+        let template = PowerGenerationSensorTemplate {
+            metadata: SensorMetadataWithLocation {
+                location: "Roof".into(),
+                ..Default::default()
+            },
+            unit: PowerUnit::Watt,
+        };
+
+        let mut sensors = Sensors::default();
+        template.to_sensor_with("230.5", &mut sensors, &test_clock());
+        assert_eq!(
+            "[{\"location\":\"Roof\",\"lastchange\":1700000000,\"unit\":\"W\",\"value\":230.5}]",
+            to_string(&sensors.power_generation).unwrap()
+        );
+    }
+}
@@ -1,9 +1,18 @@
 //! Module providing radiation sensor functionality.
 
-use super::SensorMetadata;
+use super::units::{convert_linear, Quantity};
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadata, SensorTemplate, SensorTemplateError,
+    SensorValue, Sensors, UnitConversionError,
+};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The average number of hours per year, used to convert between dose rates per hour and per year.
+const HOURS_PER_YEAR: f64 = 365.25 * 24.0;
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RadiationSensors {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub alpha: Option<Vec<RadiationSensor>>,
@@ -15,7 +24,58 @@ pub struct RadiationSensors {
     pub beta_gamma: Option<Vec<RadiationSensor>>,
 }
 
+/// The kind of radiation measured, i.e. the list in `RadiationSensors` a sensor belongs to.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RadiationSensorKind {
+    Alpha,
+    Beta,
+    Gamma,
+    BetaGamma,
+}
+
+impl RadiationSensorKind {
+    /// The key of the sensor list in `RadiationSensors`, e.g. `beta_gamma`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RadiationSensorKind::Alpha => "alpha",
+            RadiationSensorKind::Beta => "beta",
+            RadiationSensorKind::Gamma => "gamma",
+            RadiationSensorKind::BetaGamma => "beta_gamma",
+        }
+    }
+}
+
+impl RadiationSensors {
+    /// Return the list of sensors measuring the given kind of radiation.
+    pub fn get(&self, kind: RadiationSensorKind) -> &Option<Vec<RadiationSensor>> {
+        match kind {
+            RadiationSensorKind::Alpha => &self.alpha,
+            RadiationSensorKind::Beta => &self.beta,
+            RadiationSensorKind::Gamma => &self.gamma,
+            RadiationSensorKind::BetaGamma => &self.beta_gamma,
+        }
+    }
+
+    /// Return the list of sensors measuring the given kind of radiation for modification.
+    pub fn get_mut(&mut self, kind: RadiationSensorKind) -> &mut Option<Vec<RadiationSensor>> {
+        match kind {
+            RadiationSensorKind::Alpha => &mut self.alpha,
+            RadiationSensorKind::Beta => &mut self.beta,
+            RadiationSensorKind::Gamma => &mut self.gamma,
+            RadiationSensorKind::BetaGamma => &mut self.beta_gamma,
+        }
+    }
+
+    /// Add a sensor to the list of the given kind of radiation.
+    pub fn push(&mut self, kind: RadiationSensorKind, sensor: RadiationSensor) {
+        self.get_mut(kind).get_or_insert(vec![]).push(sensor);
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RadiationSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadata,
@@ -27,7 +87,122 @@ pub struct RadiationSensor {
     pub value: f64,
 }
 
+impl RadiationSensor {
+    /// Correct an observed count rate for the dead time of the counter.
+    ///
+    /// The dead time is given in µs, so the actual counts per minute are
+    /// `ocpm / (1 - ocpm * dead_time / 60_000_000)`.
+    fn actual_counts_per_minute(&self, observed: f64) -> Result<f64, UnitConversionError> {
+        let dead_time = match self.dead_time {
+            Some(dead_time) => dead_time / 60_000_000.0,
+            None => return Ok(observed),
+        };
+        let live_fraction = 1.0 - observed * dead_time;
+        if live_fraction <= 0.0 {
+            return Err(UnitConversionError::DeadTimeSaturated);
+        }
+        Ok(observed / live_fraction)
+    }
+
+    /// Reverse the dead time correction of `actual_counts_per_minute`.
+    fn observed_counts_per_minute(&self, actual: f64) -> f64 {
+        let dead_time = self.dead_time.unwrap_or_default() / 60_000_000.0;
+        actual / (1.0 + actual * dead_time)
+    }
+
+    fn conversion_factor(&self, to: &RadiationSensorUnit) -> Result<f64, UnitConversionError> {
+        match self.conversion_factor {
+            Some(factor) if factor.is_finite() && factor > 0.0 => Ok(factor),
+            Some(_) => Err(UnitConversionError::InvalidConversionFactor),
+            None => Err(UnitConversionError::MissingConversionFactor {
+                from: self.unit.to_string(),
+                to: to.to_string(),
+            }),
+        }
+    }
+
+    /// Return the value of this sensor in the given unit.
+    ///
+    /// Dose rates can be converted into each other. Counts per minute are
+    /// converted to dose rates by correcting them for the `dead_time` of the
+    /// counter and multiplying them with the `conversion_factor`, which is
+    /// expected in µSv/h per count per minute. Conversions from or to `r/h`,
+    /// which measures exposure instead of dose, fail. So do conversions using
+    /// a conversion factor that is not positive.
+    pub fn value_in(&self, unit: RadiationSensorUnit) -> Result<f64, UnitConversionError> {
+        use RadiationSensorUnit::{CountsPerMinute, MicroSievertsPerHour};
+
+        let scale = |unit: &RadiationSensorUnit| Some(unit.scale());
+        if self.unit == CountsPerMinute && unit.is_dose_rate() {
+            let factor = self.conversion_factor(&unit)?;
+            let dose_rate = self.actual_counts_per_minute(self.value)? * factor;
+            convert_linear(dose_rate, &MicroSievertsPerHour, &unit, scale)
+        } else if self.unit.is_dose_rate() && unit == CountsPerMinute {
+            let factor = self.conversion_factor(&unit)?;
+            let dose_rate = convert_linear(self.value, &self.unit, &MicroSievertsPerHour, scale)?;
+            Ok(self.observed_counts_per_minute(dose_rate / factor))
+        } else {
+            convert_linear(self.value, &self.unit, &unit, scale)
+        }
+    }
+}
+
+/// Template for a `RadiationSensor`.
+///
+/// Sensors created from this template are added to the list of `kind` in
+/// `Sensors::radiation`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RadiationSensorTemplate {
+    pub kind: RadiationSensorKind,
+    #[serde(flatten)]
+    pub metadata: SensorMetadata,
+    pub dead_time: Option<f64>,
+    pub conversion_factor: Option<f64>,
+    pub unit: RadiationSensorUnit,
+}
+
+impl FromSensorTemplate<RadiationSensorTemplate> for RadiationSensor {
+    fn try_from_template_with(
+        template: &RadiationSensorTemplate,
+        value: &SensorValue,
+        clock: &dyn Clock,
+    ) -> Result<Self, SensorTemplateError> {
+        with_context(
+            SensorKind::Radiation(template.kind),
+            &template.metadata,
+            value,
+            clock,
+            || {
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    dead_time: template.dead_time,
+                    conversion_factor: template.conversion_factor,
+                    unit: template.unit.clone(),
+                    value: value.parse()?,
+                })
+            },
+        )
+    }
+}
+
+impl SensorTemplate for RadiationSensorTemplate {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
+        let sensor = RadiationSensor::try_from_template_with(self, value, clock)?;
+        sensors
+            .radiation
+            .get_or_insert_with(Default::default)
+            .push(self.kind, sensor);
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum RadiationSensorUnit {
     #[serde(rename = "cpm")]
     CountsPerMinute,
@@ -37,13 +212,52 @@ pub enum RadiationSensorUnit {
     MicroSievertsPerHour,
     #[serde(rename = "µSv/a")]
     MicroSievertsPerYear,
-    #[serde(rename = "mSv/h")]
+    #[serde(rename = "mSv/a", alias = "mSv/h")]
     MilliSievertsPerYear,
 }
 
+impl RadiationSensorUnit {
+    /// The textual representation used by the specification.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RadiationSensorUnit::CountsPerMinute => "cpm",
+            RadiationSensorUnit::RadsPerHour => "r/h",
+            RadiationSensorUnit::MicroSievertsPerHour => "µSv/h",
+            RadiationSensorUnit::MicroSievertsPerYear => "µSv/a",
+            RadiationSensorUnit::MilliSievertsPerYear => "mSv/a",
+        }
+    }
+
+    fn scale(&self) -> (Quantity, f64) {
+        match self {
+            RadiationSensorUnit::CountsPerMinute => (Quantity::CountRate, 1.0),
+            RadiationSensorUnit::RadsPerHour => (Quantity::ExposureRate, 1.0),
+            RadiationSensorUnit::MicroSievertsPerHour => (Quantity::DoseRate, 1.0),
+            RadiationSensorUnit::MicroSievertsPerYear => (Quantity::DoseRate, 1.0 / HOURS_PER_YEAR),
+            RadiationSensorUnit::MilliSievertsPerYear => (Quantity::DoseRate, 1000.0 / HOURS_PER_YEAR),
+        }
+    }
+
+    fn is_dose_rate(&self) -> bool {
+        match self {
+            RadiationSensorUnit::MicroSievertsPerHour
+            | RadiationSensorUnit::MicroSievertsPerYear
+            | RadiationSensorUnit::MilliSievertsPerYear => true,
+            RadiationSensorUnit::CountsPerMinute | RadiationSensorUnit::RadsPerHour => false,
+        }
+    }
+}
+
+impl fmt::Display for RadiationSensorUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::sensors::test_clock;
 
     #[test]
     fn test_serialize() {
@@ -63,4 +277,131 @@ mod test {
             serde_json::to_string(&sensors).unwrap()
         );
     }
+
+    #[test]
+    fn test_template() {
+        let template = RadiationSensorTemplate {
+            kind: RadiationSensorKind::BetaGamma,
+            metadata: SensorMetadata::default(),
+            dead_time: None,
+            conversion_factor: Some(0.0057),
+            unit: RadiationSensorUnit::CountsPerMinute,
+        };
+
+        let mut sensors = Sensors::default();
+        template.to_sensor_with("18", &mut sensors, &test_clock());
+
+        assert_eq!(
+            "{\"beta_gamma\":[{\"lastchange\":1700000000,\"conversion_factor\":0.0057,\"unit\":\"cpm\",\"value\":18.0}]}",
+            serde_json::to_string(&sensors.radiation).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_deserialize_legacy_unit() {
+        let unit: RadiationSensorUnit = serde_json::from_str("\"mSv/h\"").unwrap();
+        assert_eq!(unit, RadiationSensorUnit::MilliSievertsPerYear);
+        assert_eq!("\"mSv/a\"", serde_json::to_string(&unit).unwrap());
+    }
+
+    fn sensor(unit: RadiationSensorUnit, value: f64) -> RadiationSensor {
+        RadiationSensor {
+            metadata: Default::default(),
+            dead_time: None,
+            conversion_factor: Some(0.0057),
+            unit,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_value_in() {
+        let counter = sensor(RadiationSensorUnit::CountsPerMinute, 20.0);
+        let dose_rate = counter
+            .value_in(RadiationSensorUnit::MicroSievertsPerHour)
+            .unwrap();
+        assert!((dose_rate - 0.114).abs() < 1e-9);
+
+        let dosimeter = sensor(RadiationSensorUnit::MicroSievertsPerHour, 0.114);
+        let counts = dosimeter.value_in(RadiationSensorUnit::CountsPerMinute).unwrap();
+        assert!((counts - 20.0).abs() < 1e-9);
+        let per_year = dosimeter
+            .value_in(RadiationSensorUnit::MilliSievertsPerYear)
+            .unwrap();
+        assert!((per_year - 0.114 * HOURS_PER_YEAR / 1000.0).abs() < 1e-9);
+
+        assert_eq!(
+            dosimeter.value_in(RadiationSensorUnit::RadsPerHour),
+            Err(UnitConversionError::Incompatible {
+                from: "µSv/h".into(),
+                to: "r/h".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_value_in_dead_time() {
+        let mut counter = sensor(RadiationSensorUnit::CountsPerMinute, 6000.0);
+        counter.dead_time = Some(1000.0);
+        // 6000 cpm are 100 counts per second, which keep the counter dead for 10% of the time
+        let dose_rate = counter
+            .value_in(RadiationSensorUnit::MicroSievertsPerHour)
+            .unwrap();
+        assert!((dose_rate - 6000.0 / 0.9 * 0.0057).abs() < 1e-9);
+
+        let dosimeter = sensor(RadiationSensorUnit::MicroSievertsPerHour, dose_rate);
+        let counts = RadiationSensor {
+            dead_time: Some(1000.0),
+            ..dosimeter
+        }
+        .value_in(RadiationSensorUnit::CountsPerMinute)
+        .unwrap();
+        assert!((counts - 6000.0).abs() < 1e-6);
+
+        counter.value = 60_000.0;
+        assert_eq!(
+            counter.value_in(RadiationSensorUnit::MicroSievertsPerHour),
+            Err(UnitConversionError::DeadTimeSaturated)
+        );
+        counter.conversion_factor = None;
+        assert_eq!(
+            counter.value_in(RadiationSensorUnit::MicroSievertsPerHour),
+            Err(UnitConversionError::MissingConversionFactor {
+                from: "cpm".into(),
+                to: "µSv/h".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_value_in_invalid_conversion_factor() {
+        for factor in [0.0, -0.0057, f64::NAN] {
+            let counter = RadiationSensor {
+                conversion_factor: Some(factor),
+                ..sensor(RadiationSensorUnit::CountsPerMinute, 20.0)
+            };
+            assert_eq!(
+                counter.value_in(RadiationSensorUnit::MicroSievertsPerHour),
+                Err(UnitConversionError::InvalidConversionFactor)
+            );
+            let dosimeter = RadiationSensor {
+                conversion_factor: Some(factor),
+                ..sensor(RadiationSensorUnit::MicroSievertsPerHour, 0.114)
+            };
+            assert_eq!(
+                dosimeter.value_in(RadiationSensorUnit::CountsPerMinute),
+                Err(UnitConversionError::InvalidConversionFactor)
+            );
+        }
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema_units() {
+        let schema = serde_json::to_value(schemars::schema_for!(RadiationSensorUnit)).unwrap();
+        assert_eq!(
+            schema["enum"],
+            serde_json::json!(["cpm", "r/h", "µSv/h", "µSv/a", "mSv/a"])
+        );
+    }
 }
@@ -0,0 +1,340 @@
+//! Module providing uniform access to sensors of all kinds.
+
+use std::any::Any;
+use std::fmt;
+
+use super::{
+    AccountBalanceSensor, BarometerSensor, BeverageSupplySensor, CarbonDioxideSensor, DoorLockedSensor,
+    GasConcentrationSensor, HumiditySensor, Metadata, NetworkConnectionsSensor, NetworkTrafficSensor,
+    ParticulateMatterSensor, PeopleNowPresentSensor, PowerConsumptionSensor, PowerGenerationSensor,
+    RadiationSensor, RadiationSensorKind, RadiationSensors, SensorKind, Sensors, TemperatureSensor,
+    TotalMemberCountSensor, WindSensor,
+};
+
+/// Common interface of all sensor structs.
+///
+/// The kind of a sensor is determined by the list it is stored in, which is
+/// why `Sensors::iter` returns it next to each sensor.
+pub trait Sensor: fmt::Debug + Send + Sync {
+    fn name(&self) -> Option<&str>;
+    fn location(&self) -> Option<&str>;
+    fn description(&self) -> Option<&str>;
+    fn lastchange(&self) -> Option<u64>;
+    fn set_lastchange(&mut self, lastchange: Option<u64>);
+
+    /// The primary value of the sensor.
+    ///
+    /// This is the `value` field for most sensors. Locked doors have the
+    /// value 1, unlocked ones 0. Wind sensors return their speed and network
+    /// traffic sensors their bits per second, or their packets per second if
+    /// the former is not present.
+    fn value(&self) -> Option<f64>;
+
+    /// The unit of the primary value, if the sensor has one.
+    fn unit(&self) -> Option<&str>;
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Implement `Sensor` for a sensor struct with a `metadata` field.
+macro_rules! impl_sensor {
+    ($sensor:ty, value: |$value_self:pat_param| $value:expr, unit: |$unit_self:pat_param| $unit:expr) => {
+        impl Sensor for $sensor {
+            fn name(&self) -> Option<&str> {
+                self.metadata.name()
+            }
+
+            fn location(&self) -> Option<&str> {
+                self.metadata.location()
+            }
+
+            fn description(&self) -> Option<&str> {
+                self.metadata.description()
+            }
+
+            fn lastchange(&self) -> Option<u64> {
+                self.metadata.lastchange()
+            }
+
+            fn set_lastchange(&mut self, lastchange: Option<u64>) {
+                self.metadata.set_lastchange(lastchange)
+            }
+
+            fn value(&self) -> Option<f64> {
+                let $value_self = self;
+                $value
+            }
+
+            fn unit(&self) -> Option<&str> {
+                let $unit_self = self;
+                $unit
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+        }
+    };
+}
+
+impl_sensor!(TemperatureSensor, value: |s| Some(s.value), unit: |s| Some(s.unit.as_str()));
+impl_sensor!(DoorLockedSensor, value: |s| Some(if s.value { 1.0 } else { 0.0 }), unit: |_| None);
+impl_sensor!(BarometerSensor, value: |s| Some(s.value), unit: |s| Some(s.unit.as_str()));
+impl_sensor!(RadiationSensor, value: |s| Some(s.value), unit: |s| Some(s.unit.as_str()));
+impl_sensor!(HumiditySensor, value: |s| Some(s.value), unit: |s| Some(s.unit.as_str()));
+impl_sensor!(BeverageSupplySensor, value: |s| Some(s.value as f64), unit: |s| Some(&s.unit));
+impl_sensor!(PowerConsumptionSensor, value: |s| Some(s.value), unit: |s| Some(s.unit.as_str()));
+impl_sensor!(
+    WindSensor,
+    value: |s| Some(s.properties.speed.value),
+    unit: |s| Some(s.properties.speed.unit.as_str())
+);
+impl_sensor!(NetworkConnectionsSensor, value: |s| Some(s.value as f64), unit: |_| None);
+impl_sensor!(AccountBalanceSensor, value: |s| Some(s.value), unit: |s| Some(&s.unit));
+impl_sensor!(TotalMemberCountSensor, value: |s| Some(s.value as f64), unit: |_| None);
+impl_sensor!(PeopleNowPresentSensor, value: |s| Some(s.value as f64), unit: |_| None);
+impl_sensor!(
+    NetworkTrafficSensor,
+    value: |s| match (&s.properties.bits_per_second, &s.properties.packets_per_second) {
+        (Some(bits), _) => Some(bits.value),
+        (None, Some(packets)) => Some(packets.value),
+        (None, None) => None,
+    },
+    unit: |s| match (&s.properties.bits_per_second, &s.properties.packets_per_second) {
+        (Some(_), _) => Some("bit/s"),
+        (None, Some(_)) => Some("packets/s"),
+        (None, None) => None,
+    }
+);
+impl_sensor!(CarbonDioxideSensor, value: |s| Some(s.value), unit: |s| Some(s.unit.as_str()));
+impl_sensor!(PowerGenerationSensor, value: |s| Some(s.value), unit: |s| Some(s.unit.as_str()));
+impl_sensor!(ParticulateMatterSensor, value: |s| Some(s.value), unit: |s| Some(&s.unit));
+impl_sensor!(GasConcentrationSensor, value: |s| Some(s.value), unit: |s| Some(&s.unit));
+
+/// Evaluate `$body` for every list of sensors in `$sensors`, with `$kind`
+/// bound to the kind of its sensors and `$list` to a reference to the list.
+macro_rules! for_each_list {
+    (ref $sensors:expr, |$kind:ident, $list:ident| $body:block) => {
+        for_each_list!(@lists [&] $sensors, $kind, $list, $body)
+    };
+    (mut $sensors:expr, |$kind:ident, $list:ident| $body:block) => {
+        for_each_list!(@lists [&mut] $sensors, $kind, $list, $body)
+    };
+    (@lists [$($ref:tt)+] $sensors:expr, $kind:ident, $list:ident, $body:block) => {{
+        let sensors = $($ref)+ *$sensors;
+        for_each_list!(@apply $kind, $list, $body,
+            SensorKind::Temperature => $($ref)+ sensors.temperature,
+            SensorKind::DoorLocked => $($ref)+ sensors.door_locked,
+            SensorKind::Barometer => $($ref)+ sensors.barometer,
+        );
+        if let Some(radiation) = $($ref)+ sensors.radiation {
+            let lists = [
+                (RadiationSensorKind::Alpha, $($ref)+ radiation.alpha),
+                (RadiationSensorKind::Beta, $($ref)+ radiation.beta),
+                (RadiationSensorKind::Gamma, $($ref)+ radiation.gamma),
+                (RadiationSensorKind::BetaGamma, $($ref)+ radiation.beta_gamma),
+            ];
+            for (radiation_kind, list) in lists {
+                if let Some($list) = list {
+                    let $kind = SensorKind::Radiation(radiation_kind);
+                    $body
+                }
+            }
+        }
+        for_each_list!(@apply $kind, $list, $body,
+            SensorKind::Humidity => $($ref)+ sensors.humidity,
+            SensorKind::BeverageSupply => $($ref)+ sensors.beverage_supply,
+            SensorKind::PowerConsumption => $($ref)+ sensors.power_consumption,
+            SensorKind::Wind => $($ref)+ sensors.wind,
+            SensorKind::NetworkConnections => $($ref)+ sensors.network_connections,
+            SensorKind::AccountBalance => $($ref)+ sensors.account_balance,
+            SensorKind::TotalMemberCount => $($ref)+ sensors.total_member_count,
+            SensorKind::PeopleNowPresent => $($ref)+ sensors.people_now_present,
+            SensorKind::NetworkTraffic => $($ref)+ sensors.network_traffic,
+            SensorKind::CarbonDioxide => $($ref)+ sensors.carbondioxide,
+            SensorKind::PowerGeneration => $($ref)+ sensors.power_generation,
+            SensorKind::ParticulateMatter => $($ref)+ sensors.particulate_matter,
+            SensorKind::GasConcentration => $($ref)+ sensors.gas_concentration,
+        );
+    }};
+    (@apply $kind:ident, $list:ident, $body:block, $($kind_value:expr => $list_value:expr,)*) => {
+        $({
+            let $kind = $kind_value;
+            let $list = $list_value;
+            $body
+        })*
+    };
+}
+
+impl Sensors {
+    /// Iterate over all sensors together with their kind.
+    ///
+    /// Sensors are returned in the order of the fields of `Sensors`.
+    pub fn iter(&self) -> impl Iterator<Item = (SensorKind, &dyn Sensor)> {
+        let mut sensors: Vec<(SensorKind, &dyn Sensor)> = vec![];
+        for_each_list!(ref self, |kind, list| {
+            sensors.extend(list.iter().map(|sensor| (kind, sensor as &dyn Sensor)));
+        });
+        sensors.into_iter()
+    }
+
+    /// Iterate mutably over all sensors together with their kind.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (SensorKind, &mut dyn Sensor)> {
+        let mut sensors: Vec<(SensorKind, &mut dyn Sensor)> = vec![];
+        for_each_list!(mut self, |kind, list| {
+            sensors.extend(list.iter_mut().map(|sensor| (kind, sensor as &mut dyn Sensor)));
+        });
+        sensors.into_iter()
+    }
+
+    /// The number of sensors of all kinds.
+    pub fn len(&self) -> usize {
+        let mut len = 0;
+        for_each_list!(ref self, |_kind, list| {
+            len += list.len();
+        });
+        len
+    }
+
+    /// Whether there are no sensors at all.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Find the first sensor of the given kind with the given name and location.
+    ///
+    /// `None` matches sensors without a name or location respectively.
+    pub fn find(&self, kind: SensorKind, name: Option<&str>, location: Option<&str>) -> Option<&dyn Sensor> {
+        self.iter()
+            .find(|(sensor_kind, sensor)| {
+                *sensor_kind == kind && sensor.name() == name && sensor.location() == location
+            })
+            .map(|(_, sensor)| sensor)
+    }
+
+    /// Keep only the sensors for which `keep` returns `true`.
+    ///
+    /// Radiation lists that become empty are set to `None`, and so is
+    /// `radiation` once none of its lists is left.
+    pub fn retain<F: FnMut(SensorKind, &dyn Sensor) -> bool>(&mut self, mut keep: F) {
+        for_each_list!(mut self, |kind, list| {
+            list.retain(|sensor| keep(kind, sensor));
+        });
+        self.prune_empty_radiation();
+    }
+
+    /// Set empty radiation lists to `None`, and `radiation` itself once all of
+    /// them are, so that it is not serialized as `"radiation":{}`.
+    pub(super) fn prune_empty_radiation(&mut self) {
+        let Some(radiation) = &mut self.radiation else {
+            return;
+        };
+        for kind in [
+            RadiationSensorKind::Alpha,
+            RadiationSensorKind::Beta,
+            RadiationSensorKind::Gamma,
+            RadiationSensorKind::BetaGamma,
+        ] {
+            let list = radiation.get_mut(kind);
+            if list.as_ref().is_some_and(Vec::is_empty) {
+                *list = None;
+            }
+        }
+        if *radiation == RadiationSensors::default() {
+            self.radiation = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::{RadiationSensorUnit, SensorMetadata, SensorMetadataWithLocation, TemperatureUnit};
+
+    fn sensors() -> Sensors {
+        let mut sensors = Sensors::default();
+        sensors.push(TemperatureSensor {
+            metadata: SensorMetadataWithLocation {
+                name: Some("Lab".into()),
+                location: "Basement".into(),
+                ..Default::default()
+            },
+            unit: TemperatureUnit::Celsius,
+            value: 19.5,
+        });
+        sensors.push((
+            RadiationSensorKind::Beta,
+            RadiationSensor {
+                metadata: SensorMetadata::default(),
+                dead_time: None,
+                conversion_factor: None,
+                unit: RadiationSensorUnit::CountsPerMinute,
+                value: 12.0,
+            },
+        ));
+        sensors.push(DoorLockedSensor {
+            metadata: SensorMetadataWithLocation {
+                location: "Front door".into(),
+                ..Default::default()
+            },
+            value: true,
+        });
+        sensors
+    }
+
+    #[test]
+    fn iterate_sensors() {
+        let sensors = sensors();
+        assert_eq!(sensors.len(), 3);
+        let summary: Vec<_> = sensors
+            .iter()
+            .map(|(kind, sensor)| (kind.to_string(), sensor.value(), sensor.unit()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("temperature".into(), Some(19.5), Some("°C")),
+                ("door_locked".into(), Some(1.0), None),
+                ("radiation.beta".into(), Some(12.0), Some("cpm")),
+            ]
+        );
+    }
+
+    #[test]
+    fn find_and_downcast() {
+        let mut sensors = sensors();
+        let sensor = sensors
+            .find(SensorKind::Temperature, Some("Lab"), Some("Basement"))
+            .unwrap();
+        let temperature = sensor.as_any().downcast_ref::<TemperatureSensor>().unwrap();
+        assert_eq!(temperature.value, 19.5);
+        assert!(sensors
+            .find(SensorKind::Temperature, None, Some("Basement"))
+            .is_none());
+
+        for (_, sensor) in sensors.iter_mut() {
+            if let Some(door) = sensor.as_any_mut().downcast_mut::<DoorLockedSensor>() {
+                door.value = false;
+            }
+        }
+        assert!(!sensors.door_locked[0].value);
+    }
+
+    #[test]
+    fn retain_sensors() {
+        let mut sensors = sensors();
+        sensors.retain(|kind, _| !matches!(kind, SensorKind::Radiation(_)));
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors.radiation, None);
+        assert!(!serde_json::to_string(&sensors).unwrap().contains("radiation"));
+
+        sensors.retain(|_, sensor| sensor.location() == Some("Front door"));
+        assert_eq!(sensors.len(), 1);
+        assert!(!sensors.is_empty());
+    }
+}
@@ -1,40 +1,61 @@
 //! Module providing temperature sensor functionality.
 
-use super::{FromSensorTemplate, SensorMetadataWithLocation, SensorTemplate, SensorTemplateError, Sensors};
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadataWithLocation, SensorTemplate,
+    SensorTemplateError, SensorValue, Sensors, TemperatureUnit, UnitConversionError,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TemperatureSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadataWithLocation,
-    pub unit: String,
+    pub unit: TemperatureUnit,
     pub value: f64,
 }
 
-#[derive(Debug, Clone)]
+impl TemperatureSensor {
+    /// Return the value of this sensor converted to the given unit.
+    pub fn value_in(&self, unit: TemperatureUnit) -> Result<f64, UnitConversionError> {
+        self.unit.convert(self.value, &unit)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct TemperatureSensorTemplate {
+    #[serde(flatten)]
     pub metadata: SensorMetadataWithLocation,
-    pub unit: String,
+    #[serde(default)]
+    pub unit: TemperatureUnit,
 }
 
 impl FromSensorTemplate<TemperatureSensorTemplate> for TemperatureSensor {
-    fn try_from_template(
+    fn try_from_template_with(
         template: &TemperatureSensorTemplate,
-        value: &str,
+        value: &SensorValue,
+        clock: &dyn Clock,
     ) -> Result<Self, SensorTemplateError> {
-        Ok(Self {
-            metadata: template.metadata.clone(),
-            unit: template.unit.clone(),
-            value: value.parse()?,
+        with_context(SensorKind::Temperature, &template.metadata, value, clock, || {
+            Ok(Self {
+                metadata: template.metadata.clone(),
+                unit: template.unit.clone(),
+                value: value.parse()?,
+            })
         })
     }
 }
 
 impl SensorTemplate for TemperatureSensorTemplate {
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
         sensors
             .temperature
-            .push(TemperatureSensor::try_from_template(self, value_str)?);
+            .push(TemperatureSensor::try_from_template_with(self, value, clock)?);
         Ok(())
     }
 }
@@ -42,6 +63,7 @@ impl SensorTemplate for TemperatureSensorTemplate {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::sensors::test_clock;
 
     #[test]
     fn test_template() {
@@ -51,14 +73,14 @@ mod test {
                 description: Some("Centre of main room on ground floor".into()),
                 ..Default::default()
             },
-            unit: "C".into(),
+            unit: TemperatureUnit::Celsius,
         };
 
         let mut sensors = Sensors::default();
-        template.to_sensor("24.1", &mut sensors);
+        template.to_sensor_with("24.1", &mut sensors, &test_clock());
 
         assert_eq!(
-            "[{\"location\":\"Main Room\",\"description\":\"Centre of main room on ground floor\",\"unit\":\"C\",\"value\":24.1}]",
+            "[{\"location\":\"Main Room\",\"description\":\"Centre of main room on ground floor\",\"lastchange\":1700000000,\"unit\":\"°C\",\"value\":24.1}]",
             serde_json::to_string(&sensors.temperature).unwrap()
         );
     }
@@ -71,7 +93,7 @@ mod test {
                 description: Some("Centre of main room on ground floor".into()),
                 ..Default::default()
             },
-            unit: "C".into(),
+            unit: TemperatureUnit::Celsius,
         };
 
         let mut sensors = Sensors::default();
@@ -79,7 +101,7 @@ mod test {
 
         assert!(result.is_err());
         assert_eq!(
-            "sensor float value cannot be parsed",
+            "temperature sensor at \"Main Room\" rejected input \"twenty four point one\": sensor float value cannot be parsed",
             result.err().unwrap().to_string()
         );
     }
@@ -1,37 +1,58 @@
 //! Module providing total member count sensor functionality.
 
-use super::{FromSensorTemplate, SensorMetadata, SensorTemplate, SensorTemplateError, Sensors};
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadata, SensorTemplate, SensorTemplateError,
+    SensorValue, Sensors,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TotalMemberCountSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadata,
     pub value: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct TotalMemberCountSensorTemplate {
+    #[serde(flatten)]
     pub metadata: SensorMetadata,
 }
 
 impl FromSensorTemplate<TotalMemberCountSensorTemplate> for TotalMemberCountSensor {
-    fn try_from_template(
+    fn try_from_template_with(
         template: &TotalMemberCountSensorTemplate,
-        value: &str,
+        value: &SensorValue,
+        clock: &dyn Clock,
     ) -> Result<Self, SensorTemplateError> {
-        Ok(Self {
-            metadata: template.metadata.clone(),
-            value: value.parse()?,
-        })
+        with_context(
+            SensorKind::TotalMemberCount,
+            &template.metadata,
+            value,
+            clock,
+            || {
+                Ok(Self {
+                    metadata: template.metadata.clone(),
+                    value: value.parse_count()?,
+                })
+            },
+        )
     }
 }
 
 impl SensorTemplate for TotalMemberCountSensorTemplate {
-    fn try_to_sensor(&self, value_str: &str, sensors: &mut Sensors) -> Result<(), SensorTemplateError> {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
         sensors
             .total_member_count
-            .push(TotalMemberCountSensor::try_from_template(self, value_str)?);
+            .push(TotalMemberCountSensor::try_from_template_with(
+                self, value, clock,
+            )?);
         Ok(())
     }
 }
@@ -0,0 +1,461 @@
+//! Module providing the units sensor values are measured in.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+
+use thiserror::Error;
+
+/// Error returned when a sensor value cannot be converted to another unit.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum UnitConversionError {
+    /// The unit is not defined by the specification, so its meaning is unknown
+    #[error("unit {0:?} is not defined by the specification")]
+    UnknownUnit(String),
+
+    /// The units measure different quantities
+    #[error("{from} cannot be converted to {to}")]
+    Incompatible { from: String, to: String },
+
+    /// The conversion depends on the sensor, but the sensor does not provide a conversion factor
+    #[error("converting {from} to {to} requires a conversion factor")]
+    MissingConversionFactor { from: String, to: String },
+
+    /// The observed count rate is too high to be corrected for the dead time of the sensor
+    #[error("observed count rate exceeds the limit given by the dead time")]
+    DeadTimeSaturated,
+
+    /// The conversion factor of the sensor is zero, negative or not finite
+    #[error("conversion factor must be a positive number")]
+    InvalidConversionFactor,
+}
+
+/// The physical quantity a unit measures. Only units of the same quantity can
+/// be converted into each other.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum Quantity {
+    Pressure,
+    RealPower,
+    ApparentPower,
+    Speed,
+    Angle,
+    Length,
+    CountRate,
+    ExposureRate,
+    DoseRate,
+}
+
+/// Convert a value between two units that measure a quantity in fixed ratios.
+///
+/// `scale` returns the quantity a unit measures and the factor to convert a
+/// value in that unit to the base unit of the quantity.
+pub(super) fn convert_linear<U: PartialEq + fmt::Display>(
+    value: f64,
+    from: &U,
+    to: &U,
+    scale: fn(&U) -> Option<(Quantity, f64)>,
+) -> Result<f64, UnitConversionError> {
+    if from == to {
+        return Ok(value);
+    }
+    let unknown = |unit: &U| UnitConversionError::UnknownUnit(unit.to_string());
+    let (from_quantity, from_factor) = scale(from).ok_or_else(|| unknown(from))?;
+    let (to_quantity, to_factor) = scale(to).ok_or_else(|| unknown(to))?;
+    if from_quantity != to_quantity {
+        return Err(UnitConversionError::Incompatible {
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+    Ok(value * from_factor / to_factor)
+}
+
+/// Define a unit enum that (de)serializes to the textual representation of
+/// the specification and keeps unknown units in an `Other` variant.
+///
+/// Additional spellings that are accepted on deserialization can be given
+/// after the canonical one, separated by `|`.
+///
+/// Units compare equal if they denote the same unit, so a known unit kept in
+/// `Other`, e.g. `Other("°C")`, equals its variant.
+macro_rules! sensor_unit {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident => $unit:literal $(| $alias:literal)*,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(serde::Serialize, serde::Deserialize, Default, Debug, Clone)]
+        #[serde(from = "String", into = "String")]
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )*
+            /// A unit that is not defined by the specification
+            Other(String),
+        }
+
+        impl $name {
+            /// The textual representation used by the specification.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $unit,)*
+                    $name::Other(unit) => unit,
+                }
+            }
+
+            /// Return the variant of a known unit, even if it is kept in `Other`.
+            fn normalized(&self) -> Cow<'_, Self> {
+                match self {
+                    $name::Other(unit) => Cow::Owned($name::from(unit.as_str())),
+                    known => Cow::Borrowed(known),
+                }
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                match (&*self.normalized(), &*other.normalized()) {
+                    ($name::Other(unit), $name::Other(other)) => unit == other,
+                    (unit, other) => mem::discriminant(unit) == mem::discriminant(other),
+                }
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                let unit = self.normalized();
+                mem::discriminant(&*unit).hash(state);
+                if let $name::Other(unit) = &*unit {
+                    unit.hash(state);
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(unit: &str) -> Self {
+                match unit {
+                    $($unit $(| $alias)* => $name::$variant,)*
+                    _ => $name::Other(unit.to_owned()),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(unit: String) -> Self {
+                match $name::from(unit.as_str()) {
+                    $name::Other(_) => $name::Other(unit),
+                    known => known,
+                }
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(unit: $name) -> Self {
+                match unit {
+                    $name::Other(unit) => unit,
+                    known => known.as_str().to_owned(),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        #[cfg(feature = "schemars")]
+        impl schemars::JsonSchema for $name {
+            fn schema_name() -> String {
+                stringify!($name).to_owned()
+            }
+
+            fn json_schema(_gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+                schemars::schema::SchemaObject {
+                    instance_type: Some(schemars::schema::InstanceType::String.into()),
+                    enum_values: Some(vec![$($unit.into()),*]),
+                    ..Default::default()
+                }
+                .into()
+            }
+        }
+    };
+}
+
+sensor_unit! {
+    /// The unit of a `TemperatureSensor`.
+    pub enum TemperatureUnit {
+        #[default]
+        Celsius => "°C",
+        Fahrenheit => "°F",
+        Kelvin => "K",
+        Delisle => "°De",
+        Newton => "°N",
+        Rankine => "°R",
+        Reaumur => "°Ré",
+        Romer => "°Rø",
+    }
+}
+
+sensor_unit! {
+    /// The unit of a `HumiditySensor`.
+    pub enum HumidityUnit {
+        #[default]
+        Percent => "%",
+    }
+}
+
+sensor_unit! {
+    /// The unit of a `BarometerSensor`.
+    ///
+    /// The specification spells hectopascal as `hPA`, the correct spelling
+    /// `hPa` is accepted as well.
+    pub enum BarometerUnit {
+        #[default]
+        Hectopascal => "hPA" | "hPa",
+    }
+}
+
+sensor_unit! {
+    /// The unit of a `PowerConsumptionSensor` or `PowerGenerationSensor`.
+    pub enum PowerUnit {
+        Milliwatt => "mW",
+        #[default]
+        Watt => "W",
+        VoltAmpere => "VA",
+    }
+}
+
+sensor_unit! {
+    /// The unit of a `CarbonDioxideSensor`.
+    pub enum CarbonDioxideUnit {
+        #[default]
+        PartsPerMillion => "ppm",
+    }
+}
+
+sensor_unit! {
+    /// The unit of a `WindSensorMeasurement`.
+    ///
+    /// Speed and gust are measured in `m/s`, `km/h` or `kn`, the direction
+    /// in `°` and the elevation in `m`.
+    pub enum WindUnit {
+        #[default]
+        MetersPerSecond => "m/s",
+        KilometersPerHour => "km/h",
+        Knots => "kn",
+        Degrees => "°",
+        Meters => "m",
+    }
+}
+
+impl TemperatureUnit {
+    /// Convert a temperature in this unit to kelvin.
+    fn kelvin_from(&self, value: f64) -> Result<f64, UnitConversionError> {
+        Ok(match &*self.normalized() {
+            TemperatureUnit::Celsius => value + 273.15,
+            TemperatureUnit::Fahrenheit => (value + 459.67) * 5.0 / 9.0,
+            TemperatureUnit::Kelvin => value,
+            TemperatureUnit::Delisle => 373.15 - value * 2.0 / 3.0,
+            TemperatureUnit::Newton => value * 100.0 / 33.0 + 273.15,
+            TemperatureUnit::Rankine => value * 5.0 / 9.0,
+            TemperatureUnit::Reaumur => value * 5.0 / 4.0 + 273.15,
+            TemperatureUnit::Romer => (value - 7.5) * 40.0 / 21.0 + 273.15,
+            TemperatureUnit::Other(unit) => return Err(UnitConversionError::UnknownUnit(unit.clone())),
+        })
+    }
+
+    /// Convert a temperature in kelvin to this unit.
+    fn kelvin_to(&self, kelvin: f64) -> Result<f64, UnitConversionError> {
+        Ok(match &*self.normalized() {
+            TemperatureUnit::Celsius => kelvin - 273.15,
+            TemperatureUnit::Fahrenheit => kelvin * 9.0 / 5.0 - 459.67,
+            TemperatureUnit::Kelvin => kelvin,
+            TemperatureUnit::Delisle => (373.15 - kelvin) * 3.0 / 2.0,
+            TemperatureUnit::Newton => (kelvin - 273.15) * 33.0 / 100.0,
+            TemperatureUnit::Rankine => kelvin * 9.0 / 5.0,
+            TemperatureUnit::Reaumur => (kelvin - 273.15) * 4.0 / 5.0,
+            TemperatureUnit::Romer => (kelvin - 273.15) * 21.0 / 40.0 + 7.5,
+            TemperatureUnit::Other(unit) => return Err(UnitConversionError::UnknownUnit(unit.clone())),
+        })
+    }
+
+    /// Convert a temperature from this unit to another one.
+    pub fn convert(&self, value: f64, to: &TemperatureUnit) -> Result<f64, UnitConversionError> {
+        if self == to {
+            return Ok(value);
+        }
+        to.kelvin_to(self.kelvin_from(value)?)
+    }
+}
+
+impl BarometerUnit {
+    fn scale(&self) -> Option<(Quantity, f64)> {
+        match &*self.normalized() {
+            BarometerUnit::Hectopascal => Some((Quantity::Pressure, 1.0)),
+            BarometerUnit::Other(_) => None,
+        }
+    }
+
+    /// Convert a pressure from this unit to another one.
+    pub fn convert(&self, value: f64, to: &BarometerUnit) -> Result<f64, UnitConversionError> {
+        convert_linear(value, self, to, Self::scale)
+    }
+}
+
+impl PowerUnit {
+    fn scale(&self) -> Option<(Quantity, f64)> {
+        match &*self.normalized() {
+            PowerUnit::Milliwatt => Some((Quantity::RealPower, 0.001)),
+            PowerUnit::Watt => Some((Quantity::RealPower, 1.0)),
+            PowerUnit::VoltAmpere => Some((Quantity::ApparentPower, 1.0)),
+            PowerUnit::Other(_) => None,
+        }
+    }
+
+    /// Convert a power from this unit to another one.
+    ///
+    /// Apparent power (`VA`) cannot be converted to real power (`mW`, `W`)
+    /// without knowing the power factor, so this conversion fails.
+    pub fn convert(&self, value: f64, to: &PowerUnit) -> Result<f64, UnitConversionError> {
+        convert_linear(value, self, to, Self::scale)
+    }
+}
+
+impl WindUnit {
+    fn scale(&self) -> Option<(Quantity, f64)> {
+        match &*self.normalized() {
+            WindUnit::MetersPerSecond => Some((Quantity::Speed, 1.0)),
+            WindUnit::KilometersPerHour => Some((Quantity::Speed, 1.0 / 3.6)),
+            WindUnit::Knots => Some((Quantity::Speed, 1852.0 / 3600.0)),
+            WindUnit::Degrees => Some((Quantity::Angle, 1.0)),
+            WindUnit::Meters => Some((Quantity::Length, 1.0)),
+            WindUnit::Other(_) => None,
+        }
+    }
+
+    /// Convert a wind measurement from this unit to another one.
+    ///
+    /// Speeds can be converted between `m/s`, `km/h` and `kn`, directions
+    /// and elevations only to their own unit.
+    pub fn convert(&self, value: f64, to: &WindUnit) -> Result<f64, UnitConversionError> {
+        convert_linear(value, self, to, Self::scale)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{from_str, to_string};
+
+    #[test]
+    fn serialize_deserialize_units() {
+        assert_eq!(to_string(&TemperatureUnit::Reaumur).unwrap(), "\"°Ré\"");
+        assert_eq!(
+            from_str::<TemperatureUnit>("\"K\"").unwrap(),
+            TemperatureUnit::Kelvin
+        );
+        assert_eq!(
+            from_str::<BarometerUnit>("\"hPa\"").unwrap(),
+            BarometerUnit::Hectopascal
+        );
+        assert_eq!(to_string(&BarometerUnit::Hectopascal).unwrap(), "\"hPA\"");
+        assert_eq!(from_str::<WindUnit>("\"kn\"").unwrap(), WindUnit::Knots);
+    }
+
+    #[test]
+    fn unknown_units() {
+        let unit: PowerUnit = from_str("\"kW\"").unwrap();
+        assert_eq!(unit, PowerUnit::Other("kW".into()));
+        assert_eq!(unit.as_str(), "kW");
+        assert_eq!(to_string(&unit).unwrap(), "\"kW\"");
+        assert_eq!(TemperatureUnit::from("C"), TemperatureUnit::Other("C".into()));
+    }
+
+    #[test]
+    fn known_units_in_other() {
+        use std::collections::HashSet;
+
+        assert_eq!(TemperatureUnit::Other("°C".into()), TemperatureUnit::Celsius);
+        assert_eq!(BarometerUnit::Other("hPa".into()), BarometerUnit::Hectopascal);
+        assert_ne!(TemperatureUnit::Other("°C".into()), TemperatureUnit::Kelvin);
+        assert_ne!(PowerUnit::Other("kW".into()), PowerUnit::Other("MW".into()));
+        let units: HashSet<_> = [TemperatureUnit::Celsius, TemperatureUnit::Other("°C".into())].into();
+        assert_eq!(units.len(), 1);
+
+        assert_close(
+            TemperatureUnit::Other("°F".into()).convert(212.0, &TemperatureUnit::Celsius),
+            100.0,
+        );
+        assert_close(
+            PowerUnit::Other("mW".into()).convert(1500.0, &PowerUnit::Watt),
+            1.5,
+        );
+    }
+
+    fn assert_close(actual: Result<f64, UnitConversionError>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn convert_temperature() {
+        use TemperatureUnit::*;
+        assert_close(Fahrenheit.convert(212.0, &Celsius), 100.0);
+        assert_close(Celsius.convert(-273.15, &Kelvin), 0.0);
+        for unit in [Fahrenheit, Kelvin, Delisle, Newton, Rankine, Reaumur, Romer] {
+            assert_close(
+                unit.convert(Celsius.convert(21.5, &unit).unwrap(), &Celsius),
+                21.5,
+            );
+        }
+        assert_eq!(
+            Other("C".into()).convert(21.5, &Celsius),
+            Err(UnitConversionError::UnknownUnit("C".into()))
+        );
+    }
+
+    #[test]
+    fn convert_linear_units() {
+        assert_close(
+            WindUnit::KilometersPerHour.convert(36.0, &WindUnit::MetersPerSecond),
+            10.0,
+        );
+        assert_close(WindUnit::Knots.convert(1.0, &WindUnit::KilometersPerHour), 1.852);
+        assert_close(PowerUnit::Milliwatt.convert(1500.0, &PowerUnit::Watt), 1.5);
+        assert_close(
+            BarometerUnit::Hectopascal.convert(1013.0, &BarometerUnit::Hectopascal),
+            1013.0,
+        );
+        assert_eq!(
+            WindUnit::Degrees.convert(90.0, &WindUnit::Meters),
+            Err(UnitConversionError::Incompatible {
+                from: "°".into(),
+                to: "m".into(),
+            })
+        );
+        assert_eq!(
+            PowerUnit::VoltAmpere
+                .convert(1.0, &PowerUnit::Watt)
+                .unwrap_err()
+                .to_string(),
+            "VA cannot be converted to W"
+        );
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema_units() {
+        let schema = serde_json::to_value(schemars::schema_for!(PowerUnit)).unwrap();
+        assert_eq!(schema["enum"], serde_json::json!(["mW", "W", "VA"]));
+    }
+}
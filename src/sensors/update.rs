@@ -0,0 +1,584 @@
+//! Module providing in-place updates of sensors identified by name and location.
+
+use std::fmt;
+use std::mem;
+use std::time::Duration;
+
+use crate::clock::{Clock, SystemClock};
+
+use super::{
+    AccountBalanceSensorTemplate, AnySensor, BarometerSensorTemplate, BeverageSupplySensorTemplate,
+    CarbonDioxideSensorTemplate, DoorLockedSensorTemplate, FromSensorTemplate,
+    GasConcentrationSensorTemplate, HumiditySensorTemplate, NetworkConnectionsSensorTemplate,
+    NetworkTrafficSensorTemplate, ParticulateMatterSensorTemplate, PeopleNowPresentSensorTemplate,
+    PowerConsumptionSensorTemplate, PowerGenerationSensorTemplate, RadiationSensorKind,
+    RadiationSensorTemplate, Sensor, SensorKind, SensorTemplateError, SensorValue, Sensors,
+    TemperatureSensorTemplate, TotalMemberCountSensorTemplate, WindSensorTemplate,
+};
+
+/// The identity of a sensor within the sensors of one kind.
+///
+/// Two sensors of the same kind are considered the same sensor if both their
+/// names and their locations are equal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SensorKey {
+    pub name: Option<String>,
+    pub location: Option<String>,
+}
+
+impl SensorKey {
+    pub fn new(name: Option<&str>, location: Option<&str>) -> Self {
+        Self {
+            name: name.map(str::to_owned),
+            location: location.map(str::to_owned),
+        }
+    }
+
+    /// Return the key of the given sensor.
+    pub fn of(sensor: &dyn Sensor) -> Self {
+        Self::new(sensor.name(), sensor.location())
+    }
+
+    pub(super) fn matches(&self, sensor: &dyn Sensor) -> bool {
+        self.name.as_deref() == sensor.name() && self.location.as_deref() == sensor.location()
+    }
+}
+
+impl fmt::Display for SensorKey {
+    /// Formats the name and location as quoted strings, e.g. `"Lab" at "Basement"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.name, &self.location) {
+            (Some(name), Some(location)) => write!(f, "{:?} at {:?}", name, location),
+            (Some(name), None) => write!(f, "{:?}", name),
+            (None, Some(location)) => write!(f, "at {:?}", location),
+            (None, None) => f.write_str("without name and location"),
+        }
+    }
+}
+
+/// Replace the sensor with the same key as `sensor`, or append it if there is none.
+fn replace_or_push<S: Sensor>(list: &mut Vec<S>, sensor: S) -> Option<S> {
+    let key = SensorKey::of(&sensor);
+    match list.iter_mut().find(|existing| key.matches(*existing)) {
+        Some(existing) => Some(mem::replace(existing, sensor)),
+        None => {
+            list.push(sensor);
+            None
+        }
+    }
+}
+
+/// Rebuild the sensor with the given key from `value`, using a template derived from the sensor.
+///
+/// The rebuilt sensor's `lastchange` is set to the time of `clock`.
+fn rebuild<S, T>(
+    list: &mut [S],
+    key: &SensorKey,
+    value: &SensorValue,
+    clock: &dyn Clock,
+    template: impl FnOnce(&S) -> T,
+) -> Result<bool, SensorTemplateError>
+where
+    S: Sensor + FromSensorTemplate<T>,
+{
+    match list.iter_mut().find(|sensor| key.matches(*sensor)) {
+        Some(sensor) => {
+            *sensor = S::try_from_template_with(&template(sensor), value, clock)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Whether the last measurement of the sensor is more than `max_age` older than `now`.
+///
+/// Sensors without `lastchange` are never stale, since their age is unknown.
+fn is_stale(sensor: &dyn Sensor, now: u64, max_age: Duration) -> bool {
+    sensor
+        .lastchange()
+        .is_some_and(|lastchange| now.saturating_sub(lastchange) > max_age.as_secs())
+}
+
+/// Remove the sensor with the given key.
+fn take<S: Sensor>(list: &mut Vec<S>, key: &SensorKey) -> Option<S> {
+    let index = list.iter().position(|sensor| key.matches(sensor))?;
+    Some(list.remove(index))
+}
+
+impl Sensors {
+    /// Add a sensor, replacing the sensor of the same kind with the same name
+    /// and location if there is one.
+    ///
+    /// Returns the replaced sensor.
+    pub fn upsert<S: Into<AnySensor>>(&mut self, sensor: S) -> Option<AnySensor> {
+        match sensor.into() {
+            AnySensor::Temperature(sensor) => {
+                replace_or_push(&mut self.temperature, sensor).map(AnySensor::Temperature)
+            }
+            AnySensor::DoorLocked(sensor) => {
+                replace_or_push(&mut self.door_locked, sensor).map(AnySensor::DoorLocked)
+            }
+            AnySensor::Barometer(sensor) => {
+                replace_or_push(&mut self.barometer, sensor).map(AnySensor::Barometer)
+            }
+            AnySensor::Radiation(kind, sensor) => {
+                let list = self
+                    .radiation
+                    .get_or_insert_with(Default::default)
+                    .get_mut(kind)
+                    .get_or_insert_with(Vec::new);
+                replace_or_push(list, sensor).map(|sensor| AnySensor::Radiation(kind, sensor))
+            }
+            AnySensor::Humidity(sensor) => {
+                replace_or_push(&mut self.humidity, sensor).map(AnySensor::Humidity)
+            }
+            AnySensor::BeverageSupply(sensor) => {
+                replace_or_push(&mut self.beverage_supply, sensor).map(AnySensor::BeverageSupply)
+            }
+            AnySensor::PowerConsumption(sensor) => {
+                replace_or_push(&mut self.power_consumption, sensor).map(AnySensor::PowerConsumption)
+            }
+            AnySensor::Wind(sensor) => replace_or_push(&mut self.wind, sensor).map(AnySensor::Wind),
+            AnySensor::NetworkConnections(sensor) => {
+                replace_or_push(&mut self.network_connections, sensor).map(AnySensor::NetworkConnections)
+            }
+            AnySensor::AccountBalance(sensor) => {
+                replace_or_push(&mut self.account_balance, sensor).map(AnySensor::AccountBalance)
+            }
+            AnySensor::TotalMemberCount(sensor) => {
+                replace_or_push(&mut self.total_member_count, sensor).map(AnySensor::TotalMemberCount)
+            }
+            AnySensor::PeopleNowPresent(sensor) => {
+                replace_or_push(&mut self.people_now_present, sensor).map(AnySensor::PeopleNowPresent)
+            }
+            AnySensor::NetworkTraffic(sensor) => {
+                replace_or_push(&mut self.network_traffic, sensor).map(AnySensor::NetworkTraffic)
+            }
+            AnySensor::CarbonDioxide(sensor) => {
+                replace_or_push(&mut self.carbondioxide, sensor).map(AnySensor::CarbonDioxide)
+            }
+            AnySensor::PowerGeneration(sensor) => {
+                replace_or_push(&mut self.power_generation, sensor).map(AnySensor::PowerGeneration)
+            }
+            AnySensor::ParticulateMatter(sensor) => {
+                replace_or_push(&mut self.particulate_matter, sensor).map(AnySensor::ParticulateMatter)
+            }
+            AnySensor::GasConcentration(sensor) => {
+                replace_or_push(&mut self.gas_concentration, sensor).map(AnySensor::GasConcentration)
+            }
+        }
+    }
+
+    /// Set the value of the sensor of the given kind with the given key.
+    ///
+    /// The value is parsed exactly like a template with the sensor's metadata
+    /// and unit would parse it, so the same validation applies. The sensor is
+    /// left untouched if the value is rejected.
+    ///
+    /// Returns `Ok(false)` if there is no such sensor.
+    pub fn set_value<V: Into<SensorValue>>(
+        &mut self,
+        kind: SensorKind,
+        key: &SensorKey,
+        value: V,
+    ) -> Result<bool, SensorTemplateError> {
+        self.set_value_with(kind, key, value, &SystemClock)
+    }
+
+    /// Like `set_value`, but with the sensor's `lastchange` taken from `clock`.
+    ///
+    /// Together with `prune_stale_with` and `mark_stale_with` this allows
+    /// sensors to be updated and expired against the same clock.
+    pub fn set_value_with<V: Into<SensorValue>>(
+        &mut self,
+        kind: SensorKind,
+        key: &SensorKey,
+        value: V,
+        clock: &dyn Clock,
+    ) -> Result<bool, SensorTemplateError> {
+        let value = &value.into();
+        match kind {
+            SensorKind::Temperature => rebuild(&mut self.temperature, key, value, clock, |sensor| {
+                TemperatureSensorTemplate {
+                    metadata: sensor.metadata.clone(),
+                    unit: sensor.unit.clone(),
+                }
+            }),
+            SensorKind::DoorLocked => rebuild(&mut self.door_locked, key, value, clock, |sensor| {
+                DoorLockedSensorTemplate {
+                    metadata: sensor.metadata.clone(),
+                }
+            }),
+            SensorKind::Barometer => rebuild(&mut self.barometer, key, value, clock, |sensor| {
+                BarometerSensorTemplate {
+                    metadata: sensor.metadata.clone(),
+                    unit: sensor.unit.clone(),
+                }
+            }),
+            SensorKind::Radiation(radiation_kind) => {
+                let list = self
+                    .radiation
+                    .as_mut()
+                    .and_then(|radiation| radiation.get_mut(radiation_kind).as_mut());
+                match list {
+                    Some(list) => rebuild(list, key, value, clock, |sensor| RadiationSensorTemplate {
+                        kind: radiation_kind,
+                        metadata: sensor.metadata.clone(),
+                        dead_time: sensor.dead_time,
+                        conversion_factor: sensor.conversion_factor,
+                        unit: sensor.unit.clone(),
+                    }),
+                    None => Ok(false),
+                }
+            }
+            SensorKind::Humidity => rebuild(&mut self.humidity, key, value, clock, |sensor| {
+                HumiditySensorTemplate {
+                    metadata: sensor.metadata.clone(),
+                    unit: sensor.unit.clone(),
+                }
+            }),
+            SensorKind::BeverageSupply => rebuild(&mut self.beverage_supply, key, value, clock, |sensor| {
+                BeverageSupplySensorTemplate {
+                    metadata: sensor.metadata.clone(),
+                    unit: sensor.unit.clone(),
+                }
+            }),
+            SensorKind::PowerConsumption => {
+                rebuild(&mut self.power_consumption, key, value, clock, |sensor| {
+                    PowerConsumptionSensorTemplate {
+                        metadata: sensor.metadata.clone(),
+                        unit: sensor.unit.clone(),
+                    }
+                })
+            }
+            SensorKind::Wind => rebuild(&mut self.wind, key, value, clock, |sensor| WindSensorTemplate {
+                metadata: sensor.metadata.clone(),
+                speed_unit: sensor.properties.speed.unit.clone(),
+            }),
+            SensorKind::NetworkConnections => {
+                rebuild(&mut self.network_connections, key, value, clock, |sensor| {
+                    NetworkConnectionsSensorTemplate {
+                        metadata: sensor.metadata.clone(),
+                        kind: sensor.kind.clone(),
+                    }
+                })
+            }
+            SensorKind::AccountBalance => rebuild(&mut self.account_balance, key, value, clock, |sensor| {
+                AccountBalanceSensorTemplate {
+                    metadata: sensor.metadata.clone(),
+                    unit: sensor.unit.clone(),
+                }
+            }),
+            SensorKind::TotalMemberCount => {
+                rebuild(&mut self.total_member_count, key, value, clock, |sensor| {
+                    TotalMemberCountSensorTemplate {
+                        metadata: sensor.metadata.clone(),
+                    }
+                })
+            }
+            SensorKind::PeopleNowPresent => {
+                rebuild(&mut self.people_now_present, key, value, clock, |sensor| {
+                    PeopleNowPresentSensorTemplate {
+                        metadata: sensor.metadata.clone(),
+                    }
+                })
+            }
+            SensorKind::NetworkTraffic => rebuild(&mut self.network_traffic, key, value, clock, |sensor| {
+                NetworkTrafficSensorTemplate {
+                    metadata: sensor.metadata.clone(),
+                    maximum_bits_per_second: sensor
+                        .properties
+                        .bits_per_second
+                        .as_ref()
+                        .and_then(|bits| bits.maximum),
+                }
+            }),
+            SensorKind::CarbonDioxide => rebuild(&mut self.carbondioxide, key, value, clock, |sensor| {
+                CarbonDioxideSensorTemplate {
+                    metadata: sensor.metadata.clone(),
+                    unit: sensor.unit.clone(),
+                }
+            }),
+            SensorKind::PowerGeneration => rebuild(&mut self.power_generation, key, value, clock, |sensor| {
+                PowerGenerationSensorTemplate {
+                    metadata: sensor.metadata.clone(),
+                    unit: sensor.unit.clone(),
+                }
+            }),
+            SensorKind::ParticulateMatter => {
+                rebuild(&mut self.particulate_matter, key, value, clock, |sensor| {
+                    ParticulateMatterSensorTemplate {
+                        metadata: sensor.metadata.clone(),
+                        size: sensor.size.clone(),
+                        unit: sensor.unit.clone(),
+                    }
+                })
+            }
+            SensorKind::GasConcentration => {
+                rebuild(&mut self.gas_concentration, key, value, clock, |sensor| {
+                    GasConcentrationSensorTemplate {
+                        metadata: sensor.metadata.clone(),
+                        gas: sensor.gas.clone(),
+                        unit: sensor.unit.clone(),
+                    }
+                })
+            }
+        }
+    }
+
+    /// Remove the sensor of the given kind with the given key.
+    ///
+    /// Radiation lists that become empty are set to `None`, and so is
+    /// `radiation` once none of its lists is left. Returns the removed sensor.
+    pub fn remove(&mut self, kind: SensorKind, key: &SensorKey) -> Option<AnySensor> {
+        match kind {
+            SensorKind::Temperature => take(&mut self.temperature, key).map(AnySensor::Temperature),
+            SensorKind::DoorLocked => take(&mut self.door_locked, key).map(AnySensor::DoorLocked),
+            SensorKind::Barometer => take(&mut self.barometer, key).map(AnySensor::Barometer),
+            SensorKind::Radiation(radiation_kind) => self.remove_radiation(radiation_kind, key),
+            SensorKind::Humidity => take(&mut self.humidity, key).map(AnySensor::Humidity),
+            SensorKind::BeverageSupply => take(&mut self.beverage_supply, key).map(AnySensor::BeverageSupply),
+            SensorKind::PowerConsumption => {
+                take(&mut self.power_consumption, key).map(AnySensor::PowerConsumption)
+            }
+            SensorKind::Wind => take(&mut self.wind, key).map(AnySensor::Wind),
+            SensorKind::NetworkConnections => {
+                take(&mut self.network_connections, key).map(AnySensor::NetworkConnections)
+            }
+            SensorKind::AccountBalance => take(&mut self.account_balance, key).map(AnySensor::AccountBalance),
+            SensorKind::TotalMemberCount => {
+                take(&mut self.total_member_count, key).map(AnySensor::TotalMemberCount)
+            }
+            SensorKind::PeopleNowPresent => {
+                take(&mut self.people_now_present, key).map(AnySensor::PeopleNowPresent)
+            }
+            SensorKind::NetworkTraffic => take(&mut self.network_traffic, key).map(AnySensor::NetworkTraffic),
+            SensorKind::CarbonDioxide => take(&mut self.carbondioxide, key).map(AnySensor::CarbonDioxide),
+            SensorKind::PowerGeneration => {
+                take(&mut self.power_generation, key).map(AnySensor::PowerGeneration)
+            }
+            SensorKind::ParticulateMatter => {
+                take(&mut self.particulate_matter, key).map(AnySensor::ParticulateMatter)
+            }
+            SensorKind::GasConcentration => {
+                take(&mut self.gas_concentration, key).map(AnySensor::GasConcentration)
+            }
+        }
+    }
+
+    /// Remove all sensors whose last measurement is more than `max_age` old.
+    ///
+    /// Sensors without `lastchange` are kept. Returns the number of removed
+    /// sensors.
+    pub fn prune_stale(&mut self, max_age: Duration) -> usize {
+        self.prune_stale_with(max_age, &SystemClock)
+    }
+
+    /// Like `prune_stale`, but with the current time taken from `clock`.
+    pub fn prune_stale_with(&mut self, max_age: Duration, clock: &dyn Clock) -> usize {
+        let now = clock.timestamp();
+        let len = self.len();
+        self.retain(|_, sensor| !is_stale(sensor, now, max_age));
+        len - self.len()
+    }
+
+    /// Return the kind and key of all sensors whose last measurement is more
+    /// than `max_age` old, without removing them.
+    ///
+    /// Use this to refresh or flag stale sensors instead of dropping them.
+    pub fn mark_stale(&self, max_age: Duration) -> Vec<(SensorKind, SensorKey)> {
+        self.mark_stale_with(max_age, &SystemClock)
+    }
+
+    /// Like `mark_stale`, but with the current time taken from `clock`.
+    pub fn mark_stale_with(&self, max_age: Duration, clock: &dyn Clock) -> Vec<(SensorKind, SensorKey)> {
+        let now = clock.timestamp();
+        self.iter()
+            .filter(|(_, sensor)| is_stale(*sensor, now, max_age))
+            .map(|(kind, sensor)| (kind, SensorKey::of(sensor)))
+            .collect()
+    }
+
+    fn remove_radiation(&mut self, kind: RadiationSensorKind, key: &SensorKey) -> Option<AnySensor> {
+        let list = self.radiation.as_mut()?.get_mut(kind);
+        let sensor = take(list.as_mut()?, key)?;
+        self.prune_empty_radiation();
+        Some(AnySensor::Radiation(kind, sensor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sensors::{DoorLockedSensor, SensorTemplate, TemperatureSensorTemplate};
+    use crate::sensors::{
+        PeopleNowPresentSensor, RadiationSensor, RadiationSensorUnit, SensorMetadata,
+        SensorMetadataWithLocation, SensorValueError, TemperatureSensor, TemperatureUnit,
+    };
+    use std::time::SystemTime;
+
+    fn temperature(location: &str, value: f64) -> TemperatureSensor {
+        TemperatureSensor {
+            metadata: SensorMetadataWithLocation {
+                location: location.into(),
+                ..Default::default()
+            },
+            unit: TemperatureUnit::Celsius,
+            value,
+        }
+    }
+
+    #[test]
+    fn upsert_sensors() {
+        let mut sensors = Sensors::default();
+        assert_eq!(sensors.upsert(temperature("Hall", 19.0)), None);
+        assert_eq!(sensors.upsert(temperature("Lab", 21.0)), None);
+        assert_eq!(
+            sensors.upsert(temperature("Hall", 19.5)),
+            Some(AnySensor::Temperature(temperature("Hall", 19.0)))
+        );
+        assert_eq!(
+            sensors.temperature,
+            [temperature("Hall", 19.5), temperature("Lab", 21.0)]
+        );
+    }
+
+    #[test]
+    fn set_sensor_values() {
+        let mut sensors = Sensors::default();
+        sensors.push(temperature("Hall", 19.0));
+        sensors.push(PeopleNowPresentSensor {
+            metadata: SensorMetadata {
+                name: Some("Members".into()),
+                ..Default::default()
+            },
+            value: 2,
+            names: Some(vec!["alice".into(), "bob".into()]),
+        });
+
+        let hall = SensorKey::new(None, Some("Hall"));
+        assert!(sensors.set_value(SensorKind::Temperature, &hall, "20.5").unwrap());
+        assert_eq!(sensors.temperature[0].value, 20.5);
+        assert!(!sensors
+            .set_value(
+                SensorKind::Temperature,
+                &SensorKey::new(None, Some("Lab")),
+                "20.5"
+            )
+            .unwrap());
+
+        let error = sensors
+            .set_value(SensorKind::Temperature, &hall, "warm")
+            .unwrap_err();
+        assert!(matches!(error.error, SensorValueError::BadFloat(_)));
+        assert_eq!(sensors.temperature[0].value, 20.5);
+
+        let members = SensorKey::new(Some("Members"), None);
+        assert!(sensors
+            .set_value(SensorKind::PeopleNowPresent, &members, vec!["carol".to_owned()])
+            .unwrap());
+        assert_eq!(sensors.people_now_present[0].value, 1);
+        assert_eq!(sensors.people_now_present[0].names, Some(vec!["carol".into()]));
+    }
+
+    #[test]
+    fn remove_sensors() {
+        let mut sensors = Sensors::default();
+        sensors.push(temperature("Hall", 19.0));
+        sensors.push((
+            RadiationSensorKind::Alpha,
+            RadiationSensor {
+                metadata: SensorMetadata::default(),
+                dead_time: None,
+                conversion_factor: None,
+                unit: RadiationSensorUnit::CountsPerMinute,
+                value: 3.0,
+            },
+        ));
+
+        let hall = SensorKey::new(None, Some("Hall"));
+        assert_eq!(
+            sensors.remove(SensorKind::Temperature, &hall),
+            Some(AnySensor::Temperature(temperature("Hall", 19.0)))
+        );
+        assert_eq!(sensors.remove(SensorKind::Temperature, &hall), None);
+
+        let alpha = SensorKind::Radiation(RadiationSensorKind::Alpha);
+        assert!(sensors.remove(alpha, &SensorKey::default()).is_some());
+        assert_eq!(sensors.radiation, None);
+        assert_eq!(serde_json::to_string(&sensors).unwrap(), "{}");
+        assert!(sensors.is_empty());
+    }
+
+    #[test]
+    fn set_value_updates_lastchange() {
+        let mut sensors = Sensors::default();
+        let mut hall = temperature("Hall", 19.0);
+        hall.metadata.lastchange = Some(1_000);
+        sensors.push(hall);
+        sensors
+            .set_value(SensorKind::Temperature, &SensorKey::new(None, Some("Hall")), "20")
+            .unwrap();
+        assert!(sensors.temperature[0].metadata.lastchange > Some(1_000));
+    }
+
+    #[test]
+    fn stale_sensors() {
+        let template = TemperatureSensorTemplate {
+            metadata: SensorMetadataWithLocation {
+                location: "Hall".into(),
+                ..Default::default()
+            },
+            unit: TemperatureUnit::Celsius,
+        };
+        let mut sensors = Sensors::default();
+        template.try_to_sensor("19.0", &mut sensors).unwrap();
+        let mut lab = temperature("Lab", 21.0);
+        lab.metadata.lastchange = Some(1_000);
+        sensors.push(lab);
+        sensors.push(DoorLockedSensor::default());
+
+        let max_age = Duration::from_secs(3600);
+        let clock = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000 + 3600);
+        assert_eq!(sensors.mark_stale_with(max_age, &clock), []);
+        assert_eq!(
+            sensors.mark_stale(max_age),
+            [(SensorKind::Temperature, SensorKey::new(None, Some("Lab")))]
+        );
+
+        assert_eq!(sensors.prune_stale(max_age), 1);
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors.temperature[0].metadata.location, "Hall");
+    }
+
+    #[test]
+    fn set_value_with_refreshes_stale_sensors() {
+        let mut sensors = Sensors::default();
+        for location in ["Hall", "Lab"] {
+            let mut sensor = temperature(location, 19.0);
+            sensor.metadata.lastchange = Some(1_000);
+            sensors.push(sensor);
+        }
+
+        let max_age = Duration::from_secs(3600);
+        let clock = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000 + 2 * 3600);
+        let hall = SensorKey::new(None, Some("Hall"));
+        assert!(sensors
+            .set_value_with(SensorKind::Temperature, &hall, "20", &clock)
+            .unwrap());
+        assert_eq!(sensors.temperature[0].metadata.lastchange, Some(1_000 + 2 * 3600));
+        assert_eq!(
+            sensors.mark_stale_with(max_age, &clock),
+            [(SensorKind::Temperature, SensorKey::new(None, Some("Lab")))]
+        );
+
+        assert_eq!(sensors.prune_stale_with(max_age, &clock), 1);
+        assert_eq!(
+            sensors.temperature,
+            [temperature("Hall", 20.0)].map(|mut sensor| {
+                sensor.metadata.lastchange = Some(1_000 + 2 * 3600);
+                sensor
+            })
+        );
+    }
+}
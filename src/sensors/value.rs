@@ -0,0 +1,233 @@
+//! Module providing the input values of sensor templates.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use super::SensorValueError;
+
+/// The input a `SensorTemplate` creates a sensor from.
+///
+/// Most sensors take a single `Scalar` value. Sensors with additional lists,
+/// like the names of the people present, also take a `List`, and sensors
+/// measuring several properties, like wind, take a `Map` of named values.
+/// `Json` values are interpreted as the matching variant, e.g. a JSON array
+/// of strings as a `List`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SensorValue {
+    Scalar(String),
+    List(Vec<String>),
+    Map(BTreeMap<String, f64>),
+    Json(Value),
+}
+
+impl SensorValue {
+    /// A description of the kind of value, used in error messages.
+    fn description(&self) -> &'static str {
+        match self {
+            SensorValue::Scalar(_) => "a single value",
+            SensorValue::List(_) => "a list",
+            SensorValue::Map(_) => "a map",
+            SensorValue::Json(Value::Null) => "JSON null",
+            SensorValue::Json(Value::Array(_)) => "a JSON array",
+            SensorValue::Json(Value::Object(_)) => "a JSON object",
+            SensorValue::Json(_) => "a single JSON value",
+        }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> SensorValueError {
+        SensorValueError::UnexpectedValue {
+            expected,
+            found: self.description(),
+        }
+    }
+
+    /// Parse a single value.
+    pub fn parse<T: FromStr>(&self) -> Result<T, SensorValueError>
+    where
+        SensorValueError: From<T::Err>,
+    {
+        match self {
+            SensorValue::Scalar(value) => Ok(value.trim().parse()?),
+            SensorValue::Json(Value::String(value)) => Ok(value.parse()?),
+            SensorValue::Json(value @ (Value::Bool(_) | Value::Number(_))) => {
+                Ok(value.to_string().parse()?)
+            }
+            _ => Err(self.unexpected("a single value")),
+        }
+    }
+
+    /// Parse a count, which must not be negative.
+    pub(super) fn parse_count(&self) -> Result<u64, SensorValueError> {
+        let count: i64 = self.parse()?;
+        check_range(count as f64, 0.0..=f64::INFINITY, "at least 0")?;
+        Ok(count as u64)
+    }
+
+    /// Return the list of strings, if this value is a list.
+    pub fn as_list(&self) -> Option<Vec<String>> {
+        match self {
+            SensorValue::List(list) => Some(list.clone()),
+            SensorValue::Json(Value::Array(array)) => array
+                .iter()
+                .map(|value| value.as_str().map(str::to_owned))
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// Return the named values, rejecting names not contained in `names`.
+    ///
+    /// Scalar values are parsed from a string like
+    /// `speed=3.2,gust=5.0,direction=180,elevation=10`.
+    pub(super) fn named_values(&self, names: &[&str]) -> Result<NamedValues, SensorValueError> {
+        let values: BTreeMap<String, f64> = match self {
+            SensorValue::Scalar(value) => {
+                let mut values = BTreeMap::new();
+                for pair in value.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+                    let (name, named_value) = pair
+                        .split_once('=')
+                        .ok_or_else(|| SensorValueError::MalformedNamedValues(value.to_owned()))?;
+                    values.insert(name.trim().to_owned(), named_value.trim().parse()?);
+                }
+                values
+            }
+            SensorValue::Map(values) => values.clone(),
+            SensorValue::Json(value @ Value::Object(_)) => serde_json::from_value(value.clone())?,
+            _ => return Err(self.unexpected("named values")),
+        };
+        if let Some(name) = values.keys().find(|name| !names.contains(&name.as_str())) {
+            return Err(SensorValueError::UnknownNamedValue(name.clone()));
+        }
+        Ok(NamedValues(values))
+    }
+}
+
+impl From<&str> for SensorValue {
+    fn from(value: &str) -> Self {
+        SensorValue::Scalar(value.to_owned())
+    }
+}
+
+impl From<String> for SensorValue {
+    fn from(value: String) -> Self {
+        SensorValue::Scalar(value)
+    }
+}
+
+impl From<Vec<String>> for SensorValue {
+    fn from(list: Vec<String>) -> Self {
+        SensorValue::List(list)
+    }
+}
+
+impl From<BTreeMap<String, f64>> for SensorValue {
+    fn from(values: BTreeMap<String, f64>) -> Self {
+        SensorValue::Map(values)
+    }
+}
+
+impl From<Value> for SensorValue {
+    fn from(value: Value) -> Self {
+        SensorValue::Json(value)
+    }
+}
+
+impl fmt::Display for SensorValue {
+    /// Formats scalars as they are, lists and named values comma separated
+    /// and JSON values as JSON.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SensorValue::Scalar(value) => f.write_str(value),
+            SensorValue::List(list) => f.write_str(&list.join(",")),
+            SensorValue::Map(values) => {
+                let pairs: Vec<_> = values
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect();
+                f.write_str(&pairs.join(","))
+            }
+            SensorValue::Json(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Fail with `SensorValueError::OutOfRange` unless `value` lies within `range`.
+pub(super) fn check_range(
+    value: f64,
+    range: RangeInclusive<f64>,
+    constraint: &'static str,
+) -> Result<f64, SensorValueError> {
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        Err(SensorValueError::OutOfRange { value, constraint })
+    }
+}
+
+/// The named values of a multi-value sensor.
+#[derive(Debug, Clone)]
+pub(super) struct NamedValues(BTreeMap<String, f64>);
+
+impl NamedValues {
+    /// Return the value with the given name, if present.
+    pub(super) fn get(&self, name: &str) -> Option<f64> {
+        self.0.get(name).copied()
+    }
+
+    /// Return the value with the given name, failing if it is not present.
+    pub(super) fn require(&self, name: &'static str) -> Result<f64, SensorValueError> {
+        self.get(name).ok_or(SensorValueError::MissingNamedValue(name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_scalar() {
+        assert_eq!(SensorValue::from(" 42 ").parse::<u64>().unwrap(), 42);
+        assert_eq!(SensorValue::from(json!(21.5)).parse::<f64>().unwrap(), 21.5);
+        assert!(SensorValue::from(json!("true")).parse::<bool>().unwrap());
+        assert_eq!(
+            SensorValue::from(vec!["a".to_owned()])
+                .parse::<u64>()
+                .unwrap_err()
+                .to_string(),
+            "sensor value must be a single value, found a list"
+        );
+    }
+
+    #[test]
+    fn named_values() {
+        let names = ["speed", "gust"];
+        let values = SensorValue::from("speed=1.5, gust=3")
+            .named_values(&names)
+            .unwrap();
+        assert_eq!(values.get("speed"), Some(1.5));
+        assert_eq!(values.get("gust"), Some(3.0));
+
+        let values = SensorValue::from(json!({"speed": 2}))
+            .named_values(&names)
+            .unwrap();
+        assert_eq!(values.get("speed"), Some(2.0));
+        assert_eq!(
+            values.require("gust").unwrap_err().to_string(),
+            "sensor value gust is missing"
+        );
+
+        let map = BTreeMap::from([("height".to_owned(), 1.0)]);
+        assert_eq!(
+            SensorValue::from(map)
+                .named_values(&names)
+                .unwrap_err()
+                .to_string(),
+            "sensor value height is unknown"
+        );
+    }
+}
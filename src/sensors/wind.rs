@@ -1,16 +1,22 @@
 //! Module providing wind sensor functionality.
 
-use super::SensorMetadataWithLocation;
+use super::value::check_range;
+use super::{
+    with_context, Clock, FromSensorTemplate, SensorKind, SensorMetadataWithLocation, SensorTemplate,
+    SensorTemplateError, SensorValue, SensorValueError, Sensors, UnitConversionError, WindUnit,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct WindSensor {
     #[serde(flatten)]
     pub metadata: SensorMetadataWithLocation,
     pub properties: WindSensorProperties,
 }
 
-#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct WindSensorProperties {
     pub speed: WindSensorMeasurement,
     pub gust: WindSensorMeasurement,
@@ -18,20 +24,190 @@ pub struct WindSensorProperties {
     pub elevation: WindSensorMeasurement,
 }
 
+impl Default for WindSensorProperties {
+    fn default() -> Self {
+        Self {
+            speed: WindSensorMeasurement::default(),
+            gust: WindSensorMeasurement::default(),
+            direction: WindSensorMeasurement {
+                unit: WindUnit::Degrees,
+                value: 0.0,
+            },
+            elevation: WindSensorMeasurement {
+                unit: WindUnit::Meters,
+                value: 0.0,
+            },
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct WindSensorMeasurement {
-    pub unit: String,
+    pub unit: WindUnit,
     pub value: f64,
 }
 
+impl WindSensorMeasurement {
+    /// Return the value of this measurement converted to the given unit.
+    pub fn value_in(&self, unit: WindUnit) -> Result<f64, UnitConversionError> {
+        self.unit.convert(self.value, &unit)
+    }
+}
+
+/// Template for a `WindSensor`.
+///
+/// The value string consists of the named values `speed`, `gust`,
+/// `direction` and `elevation`, e.g. `speed=3.2,gust=5.0,direction=180,elevation=10`.
+/// Speed and gust are measured in `speed_unit`, the direction in degrees and
+/// the elevation in meters.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WindSensorTemplate {
+    #[serde(flatten)]
+    pub metadata: SensorMetadataWithLocation,
+    #[serde(default)]
+    pub speed_unit: WindUnit,
+}
+
+impl FromSensorTemplate<WindSensorTemplate> for WindSensor {
+    fn try_from_template_with(
+        template: &WindSensorTemplate,
+        value: &SensorValue,
+        clock: &dyn Clock,
+    ) -> Result<Self, SensorTemplateError> {
+        with_context(SensorKind::Wind, &template.metadata, value, clock, || {
+            let values = value.named_values(&["speed", "gust", "direction", "elevation"])?;
+            let measurement = |name, unit: &WindUnit, range, constraint| -> Result<_, SensorValueError> {
+                Ok(WindSensorMeasurement {
+                    unit: unit.clone(),
+                    value: check_range(values.require(name)?, range, constraint)?,
+                })
+            };
+            let speed_unit = &template.speed_unit;
+            Ok(Self {
+                metadata: template.metadata.clone(),
+                properties: WindSensorProperties {
+                    speed: measurement("speed", speed_unit, 0.0..=f64::INFINITY, "at least 0")?,
+                    gust: measurement("gust", speed_unit, 0.0..=f64::INFINITY, "at least 0")?,
+                    direction: measurement(
+                        "direction",
+                        &WindUnit::Degrees,
+                        0.0..=360.0,
+                        "between 0 and 360",
+                    )?,
+                    elevation: measurement("elevation", &WindUnit::Meters, f64::MIN..=f64::MAX, "a number")?,
+                },
+            })
+        })
+    }
+}
+
+impl SensorTemplate for WindSensorTemplate {
+    fn try_to_sensor_value_with(
+        &self,
+        value: &SensorValue,
+        sensors: &mut Sensors,
+        clock: &dyn Clock,
+    ) -> Result<(), SensorTemplateError> {
+        sensors
+            .wind
+            .push(WindSensor::try_from_template_with(self, value, clock)?);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::sensors::test_clock;
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_serialize() {
         let sensor = WindSensor::default();
 
-        assert_eq!("{\"location\":\"\",\"properties\":{\"speed\":{\"unit\":\"\",\"value\":0.0},\"gust\":{\"unit\":\"\",\"value\":0.0},\"direction\":{\"unit\":\"\",\"value\":0.0},\"elevation\":{\"unit\":\"\",\"value\":0.0}}}", serde_json::to_string(&sensor).unwrap());
+        assert_eq!("{\"location\":\"\",\"properties\":{\"speed\":{\"unit\":\"m/s\",\"value\":0.0},\"gust\":{\"unit\":\"m/s\",\"value\":0.0},\"direction\":{\"unit\":\"°\",\"value\":0.0},\"elevation\":{\"unit\":\"m\",\"value\":0.0}}}", serde_json::to_string(&sensor).unwrap());
+    }
+
+    fn template() -> WindSensorTemplate {
+        WindSensorTemplate {
+            metadata: SensorMetadataWithLocation {
+                location: "Roof".into(),
+                ..Default::default()
+            },
+            speed_unit: WindUnit::KilometersPerHour,
+        }
+    }
+
+    #[test]
+    fn test_template() {
+        let mut sensors = Sensors::default();
+        template().to_sensor_with(
+            "speed=12.5, gust=20,direction=270,elevation=15",
+            &mut sensors,
+            &test_clock(),
+        );
+
+        assert_eq!(
+            "[{\"location\":\"Roof\",\"lastchange\":1700000000,\"properties\":{\"speed\":{\"unit\":\"km/h\",\"value\":12.5},\"gust\":{\"unit\":\"km/h\",\"value\":20.0},\"direction\":{\"unit\":\"°\",\"value\":270.0},\"elevation\":{\"unit\":\"m\",\"value\":15.0}}}]",
+            serde_json::to_string(&sensors.wind).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_template_map() {
+        let values = [
+            ("speed", 1.0),
+            ("gust", 2.0),
+            ("direction", 90.0),
+            ("elevation", 3.0),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_owned(), value))
+        .collect::<BTreeMap<_, _>>();
+        let mut sensors = Sensors::default();
+        template()
+            .try_to_sensor_value(&values.into(), &mut sensors)
+            .unwrap();
+
+        assert_eq!(sensors.wind[0].properties.direction.value, 90.0);
+    }
+
+    #[test]
+    fn test_template_bad_named_values() {
+        let mut sensors = Sensors::default();
+        let errors = [
+            (
+                "speed=12.5,gust=20,direction=270",
+                "sensor value elevation is missing",
+            ),
+            (
+                "speed 12.5,gust=20",
+                "sensor value \"speed 12.5,gust=20\" is not a list of name=value pairs",
+            ),
+            (
+                "speed=1,gust=2,direction=3,elevation=4,height=5",
+                "sensor value height is unknown",
+            ),
+            (
+                "speed=fast,gust=2,direction=3,elevation=4",
+                "sensor float value cannot be parsed",
+            ),
+            (
+                "speed=1,gust=2,direction=400,elevation=4",
+                "sensor value 400 must be between 0 and 360",
+            ),
+            (
+                "speed=-1,gust=2,direction=3,elevation=4",
+                "sensor value -1 must be at least 0",
+            ),
+        ];
+        for (value, error) in errors {
+            let result = template().try_to_sensor(value, &mut sensors);
+            let result = result.err().unwrap();
+            assert_eq!(result.input, value);
+            assert_eq!(error, result.error.to_string());
+        }
+        assert!(sensors.wind.is_empty());
     }
 }
@@ -1,13 +1,21 @@
 use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
 use serde::{Deserialize, Serialize};
 use serde_json::value::Value;
+use thiserror::Error;
 
-use crate::sensors::Sensors;
+use crate::clock::{Clock, SystemClock};
+use crate::extensions::extension_key;
+use crate::sensors::{AnySensor, Sensors};
 
 type Extensions = BTreeMap<String, Value>;
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Location {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<String>,
@@ -15,9 +23,26 @@ pub struct Location {
     pub lon: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub areas: Option<Vec<Area>>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Area {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub square_meters: f64,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Spacefed {
     pub spacenet: bool,
     pub spacesaml: bool,
@@ -25,25 +50,32 @@ pub struct Spacefed {
 }
 
 impl Spacefed {
-    fn verify(&self, version: StatusBuilderVersion) -> Result<(), String> {
-        if version == StatusBuilderVersion::V14 {
+    fn verify(&self, version: SpecVersion, oldest: SpecVersion, errors: &mut Vec<BuildError>) {
+        if oldest >= SpecVersion::V14 {
             if self.spacephone.is_some() {
-                return Err("spacefed.spacephone key was removed".into());
+                errors.push(BuildError::RemovedField {
+                    field: "spacefed.spacephone",
+                    version: SpecVersion::V14,
+                });
             }
-        } else if self.spacephone.is_none() {
-            return Err("spacefed.spacephone must be present".into());
+        } else if version == SpecVersion::V0_13 && self.spacephone.is_none() {
+            errors.push(BuildError::RequiredField {
+                field: "spacefed.spacephone",
+                version: SpecVersion::V0_13,
+            });
         }
-        Ok(())
     }
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Icon {
     pub open: String,
     pub closed: String,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct State {
     pub open: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -56,13 +88,74 @@ pub struct State {
     pub icon: Option<Icon>,
 }
 
+/// A change of the open state, as returned by `State::transition`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChange {
+    /// The open state before the change, `None` if it was unknown
+    pub previous: Option<bool>,
+    /// The `lastchange` before the change
+    pub previous_lastchange: Option<u64>,
+    pub open: bool,
+    /// The time of the change, which is the new `lastchange`
+    pub lastchange: u64,
+    pub trigger_person: Option<String>,
+    pub message: Option<String>,
+}
+
 impl State {
-    fn verify(&self, _version: StatusBuilderVersion) -> Result<(), String> {
-        Ok(())
+    /// Set the open state, updating `lastchange`, `trigger_person` and
+    /// `message` if it changed.
+    ///
+    /// If the space already was in the given state, the state is left
+    /// untouched, including its message, and `None` is returned. So a change
+    /// is returned exactly when the state was modified.
+    pub fn transition(
+        &mut self,
+        open: bool,
+        trigger: Option<&str>,
+        message: Option<&str>,
+    ) -> Option<StateChange> {
+        self.transition_with(open, trigger, message, &SystemClock)
+    }
+
+    /// Like `transition`, but with the time of the change taken from `clock`.
+    pub fn transition_with(
+        &mut self,
+        open: bool,
+        trigger: Option<&str>,
+        message: Option<&str>,
+        clock: &dyn Clock,
+    ) -> Option<StateChange> {
+        if self.open == Some(open) {
+            return None;
+        }
+        let previous = self.open.replace(open);
+        let lastchange = clock.timestamp();
+        let previous_lastchange = self.lastchange.replace(lastchange);
+        self.trigger_person = trigger.map(str::to_owned);
+        self.message = message.map(str::to_owned);
+        Some(StateChange {
+            previous,
+            previous_lastchange,
+            open,
+            lastchange,
+            trigger_person: self.trigger_person.clone(),
+            message: self.message.clone(),
+        })
+    }
+
+    fn verify(&self, version: SpecVersion, errors: &mut Vec<BuildError>) {
+        if version >= SpecVersion::V14 && self.open.is_none() {
+            errors.push(BuildError::RequiredField {
+                field: "state.open",
+                version: SpecVersion::V14,
+            });
+        }
     }
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Event {
     pub name: String,
     #[serde(rename = "type")]
@@ -73,6 +166,7 @@ pub struct Event {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Keymaster {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -91,12 +185,14 @@ pub struct Keymaster {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GoogleContact {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub plus: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Contact {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub phone: Option<String>,
@@ -134,9 +230,14 @@ pub struct Contact {
     pub mastodon: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gopher: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telegram: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Feed {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub type_: Option<String>,
@@ -144,6 +245,7 @@ pub struct Feed {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Feeds {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub blog: Option<Feed>,
@@ -155,12 +257,154 @@ pub struct Feeds {
     pub flickr: Option<Feed>,
 }
 
+/// The interval in which a space wants its status to be fetched by directory crawlers.
+///
+/// Serialized in the format of the specification, e.g. `m.02` for every two
+/// minutes or `d.01` for once a day.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum CacheSchedule {
+    #[serde(rename = "m.02")]
+    Minutes2,
+    #[serde(rename = "m.05")]
+    Minutes5,
+    #[serde(rename = "m.10")]
+    Minutes10,
+    #[serde(rename = "m.15")]
+    Minutes15,
+    #[serde(rename = "m.30")]
+    Minutes30,
+    #[serde(rename = "h.01")]
+    Hours1,
+    #[serde(rename = "h.02")]
+    Hours2,
+    #[serde(rename = "h.04")]
+    Hours4,
+    #[serde(rename = "h.08")]
+    Hours8,
+    #[serde(rename = "h.12")]
+    Hours12,
+    #[serde(rename = "d.01")]
+    Days1,
+}
+
+impl CacheSchedule {
+    /// All schedules defined by the specification, from the shortest to the longest interval.
+    pub const ALL: [CacheSchedule; 11] = [
+        CacheSchedule::Minutes2,
+        CacheSchedule::Minutes5,
+        CacheSchedule::Minutes10,
+        CacheSchedule::Minutes15,
+        CacheSchedule::Minutes30,
+        CacheSchedule::Hours1,
+        CacheSchedule::Hours2,
+        CacheSchedule::Hours4,
+        CacheSchedule::Hours8,
+        CacheSchedule::Hours12,
+        CacheSchedule::Days1,
+    ];
+
+    /// The textual representation used by the specification, e.g. `m.02`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheSchedule::Minutes2 => "m.02",
+            CacheSchedule::Minutes5 => "m.05",
+            CacheSchedule::Minutes10 => "m.10",
+            CacheSchedule::Minutes15 => "m.15",
+            CacheSchedule::Minutes30 => "m.30",
+            CacheSchedule::Hours1 => "h.01",
+            CacheSchedule::Hours2 => "h.02",
+            CacheSchedule::Hours4 => "h.04",
+            CacheSchedule::Hours8 => "h.08",
+            CacheSchedule::Hours12 => "h.12",
+            CacheSchedule::Days1 => "d.01",
+        }
+    }
+
+    /// The interval as a `Duration`.
+    pub fn to_duration(self) -> Duration {
+        let minutes = match self {
+            CacheSchedule::Minutes2 => 2,
+            CacheSchedule::Minutes5 => 5,
+            CacheSchedule::Minutes10 => 10,
+            CacheSchedule::Minutes15 => 15,
+            CacheSchedule::Minutes30 => 30,
+            CacheSchedule::Hours1 => 60,
+            CacheSchedule::Hours2 => 2 * 60,
+            CacheSchedule::Hours4 => 4 * 60,
+            CacheSchedule::Hours8 => 8 * 60,
+            CacheSchedule::Hours12 => 12 * 60,
+            CacheSchedule::Days1 => 24 * 60,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+
+    /// The point in time at which a document fetched at `last_fetch` should be fetched again.
+    pub fn next_fetch_after(self, last_fetch: SystemTime) -> SystemTime {
+        last_fetch + self.to_duration()
+    }
+}
+
+impl From<CacheSchedule> for Duration {
+    fn from(schedule: CacheSchedule) -> Self {
+        schedule.to_duration()
+    }
+}
+
+impl fmt::Display for CacheSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The error returned when parsing an invalid `CacheSchedule`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid cache schedule {0:?}")]
+pub struct ParseCacheScheduleError(String);
+
+impl FromStr for CacheSchedule {
+    type Err = ParseCacheScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CacheSchedule::ALL
+            .into_iter()
+            .find(|schedule| schedule.as_str() == s)
+            .ok_or_else(|| ParseCacheScheduleError(s.to_owned()))
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Cache {
     pub schedule: String,
 }
 
+impl Cache {
+    /// Parse `schedule`, which fails for values not defined by the specification.
+    pub fn schedule(&self) -> Result<CacheSchedule, ParseCacheScheduleError> {
+        self.schedule.parse()
+    }
+
+    fn verify(&self, errors: &mut Vec<BuildError>) {
+        if self.schedule().is_err() {
+            errors.push(BuildError::InvalidValue {
+                field: "cache.schedule",
+                value: self.schedule.clone(),
+            });
+        }
+    }
+}
+
+impl From<CacheSchedule> for Cache {
+    fn from(schedule: CacheSchedule) -> Self {
+        Cache {
+            schedule: schedule.as_str().to_owned(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RadioShow {
     pub name: String,
     pub url: String,
@@ -171,6 +415,7 @@ pub struct RadioShow {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum IssueReportChannel {
     Email,
@@ -180,6 +425,7 @@ pub enum IssueReportChannel {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Stream {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub m4: Option<String>,
@@ -190,6 +436,7 @@ pub struct Stream {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Link {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -198,6 +445,7 @@ pub struct Link {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum BillingInterval {
     Yearly,
@@ -210,6 +458,7 @@ pub enum BillingInterval {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MembershipPlan {
     pub name: String,
     pub value: f64,
@@ -219,14 +468,196 @@ pub struct MembershipPlan {
     pub description: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct LinkedSpace {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ApiVersion {
     #[serde(rename = "14")]
     V14,
+    #[serde(rename = "15")]
+    V15,
+}
+
+/// A version of the SpaceAPI specification.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SpecVersion {
+    V0_13,
+    V14,
+    V15,
+}
+
+impl fmt::Display for SpecVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecVersion::V0_13 => f.write_str("v0.13"),
+            SpecVersion::V14 => f.write_str("v14"),
+            SpecVersion::V15 => f.write_str("v15"),
+        }
+    }
+}
+
+impl SpecVersion {
+    /// The `api_compatibility` entry declaring this version, if there is one.
+    pub fn api_version(self) -> Option<ApiVersion> {
+        match self {
+            SpecVersion::V0_13 => None,
+            SpecVersion::V14 => Some(ApiVersion::V14),
+            SpecVersion::V15 => Some(ApiVersion::V15),
+        }
+    }
+}
+
+/// The oldest of the given versions, which decides which fields may still be present.
+pub(crate) fn oldest(versions: &[SpecVersion]) -> SpecVersion {
+    versions.iter().copied().min().unwrap_or(SpecVersion::V0_13)
+}
+
+/// Describes a single problem detected when building or validating a `Status`.
+///
+/// The `field` of every variant is the dotted path of the offending key, e.g.
+/// `contact.jabber`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// A field required by the builder was never set
+    #[error("{field} missing")]
+    MissingField { field: &'static str },
+
+    /// A field required by the given version is absent
+    #[error("{field} must be present in {version}")]
+    RequiredField {
+        field: &'static str,
+        version: SpecVersion,
+    },
+
+    /// A field that was removed in the given version is present
+    #[error("{field} key was removed in {version}")]
+    RemovedField {
+        field: &'static str,
+        version: SpecVersion,
+    },
+
+    /// A field that only exists starting with the given version is present
+    #[error("{field} is only present in {version} and above")]
+    UnavailableField {
+        field: &'static str,
+        version: SpecVersion,
+    },
+
+    /// A field that was renamed in the given version is present under its old name
+    #[error("{field} key was renamed to {renamed_to} in {version}")]
+    RenamedField {
+        field: &'static str,
+        renamed_to: &'static str,
+        version: SpecVersion,
+    },
+
+    /// A list that must contain at least one entry in the given version is empty
+    #[error("{field} must not be empty in {version}")]
+    EmptyList {
+        field: &'static str,
+        version: SpecVersion,
+    },
+
+    /// A version field contains a version this crate does not know about
+    #[error("{field} declares unsupported version {value:?}")]
+    UnsupportedVersion { field: &'static str, value: String },
+
+    /// A field contains a value that is not allowed by the specification
+    #[error("{field} has invalid value {value:?}")]
+    InvalidValue { field: &'static str, value: String },
+}
+
+impl BuildError {
+    /// The dotted path of the field this error refers to.
+    pub fn field(&self) -> &'static str {
+        match self {
+            BuildError::MissingField { field }
+            | BuildError::RequiredField { field, .. }
+            | BuildError::RemovedField { field, .. }
+            | BuildError::UnavailableField { field, .. }
+            | BuildError::RenamedField { field, .. }
+            | BuildError::EmptyList { field, .. }
+            | BuildError::UnsupportedVersion { field, .. }
+            | BuildError::InvalidValue { field, .. } => field,
+        }
+    }
+
+    /// The JSON pointer (RFC 6901) of the field this error refers to, e.g.
+    /// `/contact/jabber`.
+    pub fn json_pointer(&self) -> String {
+        self.field()
+            .split('.')
+            .fold(String::new(), |mut pointer, segment| {
+                pointer.push('/');
+                pointer.push_str(segment);
+                pointer
+            })
+    }
+}
+
+/// All problems detected when building a `Status`.
+///
+/// Dereferences to a slice of `BuildError`, so the individual errors can be
+/// inspected, counted or iterated over.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct BuildErrors(pub(crate) Vec<BuildError>);
+
+impl BuildErrors {
+    /// Return the individual errors.
+    pub fn into_vec(self) -> Vec<BuildError> {
+        self.0
+    }
+}
+
+impl Deref for BuildErrors {
+    type Target = [BuildError];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl IntoIterator for BuildErrors {
+    type Item = BuildError;
+    type IntoIter = std::vec::IntoIter<BuildError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a BuildErrors {
+    type Item = &'a BuildError;
+    type IntoIter = std::slice::Iter<'a, BuildError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for BuildErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
 }
 
 /// The main SpaceAPI status object.
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Status {
     // Hackerspace properties
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -260,6 +691,8 @@ pub struct Status {
     pub links: Option<Vec<Link>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub membership_plans: Option<Vec<MembershipPlan>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linked_spaces: Option<Vec<LinkedSpace>>,
 
     // SpaceAPI internal usage
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -278,14 +711,193 @@ pub struct Status {
     pub extensions: Extensions,
 }
 
+impl Status {
+    /// Return all keys captured in `extensions` that lack the `ext_` prefix.
+    ///
+    /// Such keys are usually misspelled SpaceAPI fields, e.g. `contacts`
+    /// instead of `contact`.
+    pub fn unknown_keys(&self) -> impl Iterator<Item = &str> {
+        self.extensions
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !key.starts_with("ext_"))
+    }
+
+    /// Set the open state of the space, see `State::transition`.
+    ///
+    /// Adds a `State` if there is none yet.
+    pub fn set_open(
+        &mut self,
+        open: bool,
+        trigger: Option<&str>,
+        message: Option<&str>,
+    ) -> Option<StateChange> {
+        self.set_open_with(open, trigger, message, &SystemClock)
+    }
+
+    /// Like `set_open`, but with the time of the change taken from `clock`.
+    pub fn set_open_with(
+        &mut self,
+        open: bool,
+        trigger: Option<&str>,
+        message: Option<&str>,
+        clock: &dyn Clock,
+    ) -> Option<StateChange> {
+        self.state
+            .get_or_insert_with(State::default)
+            .transition_with(open, trigger, message, clock)
+    }
+
+    /// Check the version specific rules for all of the given versions.
+    ///
+    /// Every error is reported once, even if the rules of several versions
+    /// detect it.
+    pub(crate) fn verify(&self, versions: &[SpecVersion], errors: &mut Vec<BuildError>) {
+        let mut version_errors = vec![];
+        for &version in versions {
+            self.verify_version(version, oldest(versions), &mut version_errors);
+            for error in version_errors.drain(..) {
+                if !errors.contains(&error) {
+                    errors.push(error);
+                }
+            }
+        }
+    }
+
+    /// Check the rules of `version` for a document declaring several versions,
+    /// the oldest of which is `oldest`.
+    ///
+    /// Fields removed in some version are only reported if all declared
+    /// versions removed them, so documents declaring several versions may keep
+    /// them for older clients.
+    pub(crate) fn verify_version(
+        &self,
+        version: SpecVersion,
+        oldest: SpecVersion,
+        errors: &mut Vec<BuildError>,
+    ) {
+        if let Some(spacefed) = &self.spacefed {
+            spacefed.verify(version, oldest, errors);
+        }
+        if let Some(state) = &self.state {
+            state.verify(version, errors);
+        }
+        if let Some(sensors) = &self.sensors {
+            sensors.verify(version, errors);
+        }
+        if let Some(cache) = &self.cache {
+            cache.verify(errors);
+        }
+
+        if oldest >= SpecVersion::V14 {
+            if self.contact.jabber.is_some() {
+                errors.push(BuildError::RenamedField {
+                    field: "contact.jabber",
+                    renamed_to: "contact.xmpp",
+                    version: SpecVersion::V14,
+                });
+            }
+            if self.contact.google.is_some() {
+                errors.push(BuildError::RemovedField {
+                    field: "contact.google",
+                    version: SpecVersion::V14,
+                });
+            }
+            if self.radio_show.is_some() {
+                errors.push(BuildError::RemovedField {
+                    field: "radio_show",
+                    version: SpecVersion::V14,
+                });
+            }
+            if !self.issue_report_channels.is_empty() {
+                errors.push(BuildError::RemovedField {
+                    field: "issue_report_channels",
+                    version: SpecVersion::V14,
+                });
+            }
+        }
+        if version == SpecVersion::V0_13 {
+            if self.issue_report_channels.is_empty() {
+                errors.push(BuildError::EmptyList {
+                    field: "issue_report_channels",
+                    version: SpecVersion::V0_13,
+                });
+            }
+            if self.state.is_none() {
+                errors.push(BuildError::RequiredField {
+                    field: "state",
+                    version: SpecVersion::V0_13,
+                });
+            }
+        }
+        if version < SpecVersion::V14 {
+            if self.location.timezone.is_some() {
+                errors.push(BuildError::UnavailableField {
+                    field: "location.timezone",
+                    version: SpecVersion::V14,
+                });
+            }
+            if self.links.is_some() {
+                errors.push(BuildError::UnavailableField {
+                    field: "links",
+                    version: SpecVersion::V14,
+                });
+            }
+            if self.membership_plans.is_some() {
+                errors.push(BuildError::UnavailableField {
+                    field: "membership_plans",
+                    version: SpecVersion::V14,
+                });
+            }
+        }
+
+        if oldest >= SpecVersion::V15 && self.stream.is_some() {
+            errors.push(BuildError::RemovedField {
+                field: "stream",
+                version: SpecVersion::V15,
+            });
+        }
+        if version < SpecVersion::V15 {
+            let v15_fields = [
+                ("location.country_code", self.location.country_code.is_some()),
+                ("location.hint", self.location.hint.is_some()),
+                ("location.areas", self.location.areas.is_some()),
+                ("contact.signal", self.contact.signal.is_some()),
+                ("contact.telegram", self.contact.telegram.is_some()),
+                ("linked_spaces", self.linked_spaces.is_some()),
+            ];
+            for (field, present) in v15_fields {
+                if present {
+                    errors.push(BuildError::UnavailableField {
+                        field,
+                        version: SpecVersion::V15,
+                    });
+                }
+            }
+        }
+    }
+}
+
 #[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
 enum StatusBuilderVersion {
     #[default]
     V0_13,
     V14,
+    V15,
     Mixed,
 }
 
+impl StatusBuilderVersion {
+    fn versions(self) -> &'static [SpecVersion] {
+        match self {
+            StatusBuilderVersion::V0_13 => &[SpecVersion::V0_13],
+            StatusBuilderVersion::V14 => &[SpecVersion::V14],
+            StatusBuilderVersion::V15 => &[SpecVersion::V15],
+            StatusBuilderVersion::Mixed => &[SpecVersion::V0_13, SpecVersion::V14],
+        }
+    }
+}
+
 /// Builder for the `Status` object.
 #[derive(Default, Debug, Clone)]
 pub struct StatusBuilder {
@@ -303,9 +915,13 @@ pub struct StatusBuilder {
     radio_show: Option<Vec<RadioShow>>,
     links: Option<Vec<Link>>,
     membership_plans: Option<Vec<MembershipPlan>>,
+    linked_spaces: Option<Vec<LinkedSpace>>,
     issue_report_channels: Vec<IssueReportChannel>,
     extensions: Extensions,
     state: Option<State>,
+    sensors: Option<Sensors>,
+    stream: Option<Stream>,
+    cache: Option<Cache>,
 }
 
 impl StatusBuilder {
@@ -332,6 +948,14 @@ impl StatusBuilder {
         }
     }
 
+    pub fn v15<S: Into<String>>(space_name: S) -> StatusBuilder {
+        StatusBuilder {
+            space: space_name.into(),
+            version: StatusBuilderVersion::V15,
+            ..Default::default()
+        }
+    }
+
     pub fn mixed<S: Into<String>>(space_name: S) -> StatusBuilder {
         StatusBuilder {
             space: space_name.into(),
@@ -400,6 +1024,34 @@ impl StatusBuilder {
         self
     }
 
+    pub fn add_linked_space(mut self, linked_space: LinkedSpace) -> Self {
+        self.linked_spaces.get_or_insert(vec![]).push(linked_space);
+        self
+    }
+
+    pub fn sensors(mut self, sensors: Sensors) -> Self {
+        self.sensors = Some(sensors);
+        self
+    }
+
+    /// Add a sensor to the list of its kind.
+    ///
+    /// Radiation sensors are added as a tuple of `RadiationSensorKind` and `RadiationSensor`.
+    pub fn add_sensor<S: Into<AnySensor>>(mut self, sensor: S) -> Self {
+        self.sensors.get_or_insert_with(Default::default).push(sensor);
+        self
+    }
+
+    pub fn stream(mut self, stream: Stream) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     pub fn add_project<S: Into<String>>(mut self, project: S) -> Self {
         self.projects.get_or_insert(vec![]).push(project.into());
         self
@@ -414,74 +1066,48 @@ impl StatusBuilder {
     ///
     /// The prefix `ext_` will automatically be prepended to the name, if not already present.
     pub fn add_extension<V: Into<Value>>(mut self, name: &str, value: V) -> Self {
-        if name.starts_with("ext_") {
-            self.extensions.insert(name.to_owned(), value.into());
-        } else {
-            self.extensions.insert(format!("ext_{}", name), value.into());
-        }
+        self.extensions.insert(extension_key(name), value.into());
         self
     }
 
-    pub fn build(self) -> Result<Status, String> {
-        let api = match self.version {
-            StatusBuilderVersion::V0_13 | StatusBuilderVersion::Mixed => Some("0.13".to_owned()),
-            _ => None,
-        };
-        let api_compatibility = match self.version {
-            StatusBuilderVersion::V14 | StatusBuilderVersion::Mixed => Some(vec![ApiVersion::V14]),
-            _ => None,
-        };
-
-        let contact = self.contact.ok_or("contact missing")?;
-        if let Some(spacefed) = &self.spacefed {
-            spacefed.verify(self.version)?;
-        }
-        if let Some(state) = &self.state {
-            state.verify(self.version)?;
-        }
-
-        if self.version == StatusBuilderVersion::V14 {
-            if contact.jabber.is_some() {
-                return Err("jabber key under contact was renamed to xmpp".into());
-            }
-            if contact.google.is_some() {
-                return Err("google key under contact was removed".into());
-            }
-            if self.radio_show.is_some() {
-                return Err("radio_show key was removed".into());
-            }
-
-            if !self.issue_report_channels.is_empty() {
-                return Err("issue_report_channels key was removed".into());
-            }
-        } else {
-            if self.issue_report_channels.is_empty() {
-                return Err("issue_report_channels must not be empty".into());
-            }
-            if self.state.is_none() {
-                return Err("state must be present in v0.13".into());
-            }
-            if let Some(ref location) = self.location {
-                if location.timezone.is_some() {
-                    return Err("location.timezone is only present in v0.14 and above".into());
-                }
-            }
-            if self.links.is_some() {
-                return Err("links is only present in v0.14 and above".into());
-            }
-            if self.membership_plans.is_some() {
-                return Err("membership_plans is only present in v0.14 and above".into());
+    /// Build the `Status` object.
+    ///
+    /// All fields are checked against the rules of the targeted SpaceAPI
+    /// version(s). If any check fails, every detected problem is returned.
+    ///
+    /// Sensor templates stamp the sensors they build with a `lastchange`,
+    /// which only v15 knows. If a version before v15 is targeted, the
+    /// `lastchange` of all sensors is therefore dropped instead of reported.
+    pub fn build(mut self) -> Result<Status, BuildErrors> {
+        let versions = self.version.versions();
+        if oldest(versions) < SpecVersion::V15 {
+            if let Some(sensors) = &mut self.sensors {
+                sensors.remove_lastchange();
             }
         }
+        let api = versions.contains(&SpecVersion::V0_13).then(|| "0.13".to_owned());
+        let api_compatibility: Vec<_> = versions.iter().filter_map(|v| v.api_version()).collect();
+        let api_compatibility = (!api_compatibility.is_empty()).then_some(api_compatibility);
+
+        let mut errors = vec![];
+        let mut require = |field: &'static str, present: bool| {
+            if !present {
+                errors.push(BuildError::MissingField { field });
+            }
+        };
+        require("contact", self.contact.is_some());
+        require("logo", self.logo.is_some());
+        require("url", self.url.is_some());
+        require("location", self.location.is_some());
 
-        Ok(Status {
+        let status = Status {
             api,
             api_compatibility,
             space: self.space,
-            logo: self.logo.ok_or("logo missing")?,
-            url: self.url.ok_or("url missing")?,
-            location: self.location.ok_or("location missing")?,
-            contact,
+            logo: self.logo.unwrap_or_default(),
+            url: self.url.unwrap_or_default(),
+            location: self.location.unwrap_or_default(),
+            contact: self.contact.unwrap_or_default(),
             spacefed: self.spacefed,
             projects: self.projects,
             cam: self.cam,
@@ -490,26 +1116,75 @@ impl StatusBuilder {
             radio_show: self.radio_show,
             links: self.links,
             membership_plans: self.membership_plans,
+            linked_spaces: self.linked_spaces,
             issue_report_channels: self.issue_report_channels,
             state: self.state,
+            sensors: self.sensors,
+            stream: self.stream,
+            cache: self.cache,
             extensions: self.extensions,
-            ..Default::default()
-        })
+        };
+        status.verify(versions, &mut errors);
+
+        if errors.is_empty() {
+            Ok(status)
+        } else {
+            Err(BuildErrors(errors))
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::sensors::{
+        CarbonDioxideSensorTemplate, CarbonDioxideUnit, ParticulateMatterSensor, SensorMetadataWithLocation,
+        SensorTemplate, TemperatureSensorTemplate, TemperatureUnit, TotalMemberCountSensor,
+    };
     use serde_json::{from_str, to_string};
 
     #[test]
     fn serialize_deserialize_cache() {
-        let a = Cache {
-            schedule: "bla".into(),
-        };
-        let b: Cache = from_str(&to_string(&a).unwrap()).unwrap();
+        let a = Cache::from(CacheSchedule::Minutes15);
+        let json = to_string(&a).unwrap();
+        assert_eq!(json, "{\"schedule\":\"m.15\"}");
+        let b: Cache = from_str(&json).unwrap();
         assert_eq!(a.schedule, b.schedule);
+        assert_eq!(b.schedule(), Ok(CacheSchedule::Minutes15));
+
+        let c: Cache = from_str("{\"schedule\":\"bla\"}").unwrap();
+        assert_eq!(c.schedule(), Err(ParseCacheScheduleError("bla".into())));
+    }
+
+    #[test]
+    fn parse_cache_schedule() {
+        for schedule in CacheSchedule::ALL {
+            assert_eq!(schedule.to_string().parse(), Ok(schedule));
+        }
+        assert_eq!(
+            "m.01".parse::<CacheSchedule>(),
+            Err(ParseCacheScheduleError("m.01".into()))
+        );
+        assert_eq!(
+            ParseCacheScheduleError("h.1".into()).to_string(),
+            "invalid cache schedule \"h.1\""
+        );
+    }
+
+    #[test]
+    fn cache_schedule_duration() {
+        assert_eq!(CacheSchedule::Minutes2.to_duration(), Duration::from_secs(120));
+        assert_eq!(
+            Duration::from(CacheSchedule::Hours12),
+            Duration::from_secs(12 * 3600)
+        );
+        assert_eq!(CacheSchedule::Days1.to_duration(), Duration::from_secs(86400));
+
+        let last_fetch = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        assert_eq!(
+            CacheSchedule::Minutes5.next_fetch_after(last_fetch),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1300)
+        );
     }
 
     #[test]
@@ -550,8 +1225,11 @@ mod test {
             .build();
         assert!(status.is_err());
         assert_eq!(
-            status.err().unwrap(),
-            "location.timezone is only present in v0.14 and above"
+            *status.err().unwrap(),
+            [BuildError::UnavailableField {
+                field: "location.timezone",
+                version: SpecVersion::V14,
+            }]
         );
     }
 
@@ -568,8 +1246,21 @@ mod test {
             })
             .add_link(Link::default())
             .build();
-        assert!(status.is_err());
-        assert_eq!(status.err().unwrap(), "links is only present in v0.14 and above");
+        let errors = status.err().unwrap();
+        assert_eq!(
+            *errors,
+            [
+                BuildError::MissingField { field: "location" },
+                BuildError::UnavailableField {
+                    field: "links",
+                    version: SpecVersion::V14,
+                },
+            ]
+        );
+        assert_eq!(
+            errors.to_string(),
+            "location missing; links is only present in v14 and above"
+        );
     }
 
     #[test]
@@ -586,10 +1277,10 @@ mod test {
             .add_membership_plan(MembershipPlan::default())
             .build();
         assert!(status.is_err());
-        assert_eq!(
-            status.err().unwrap(),
-            "membership_plans is only present in v0.14 and above"
-        );
+        assert!(status.err().unwrap().contains(&BuildError::UnavailableField {
+            field: "membership_plans",
+            version: SpecVersion::V14,
+        }));
     }
 
     #[test]
@@ -630,7 +1321,14 @@ mod test {
                 ..Contact::default()
             })
             .build();
-        assert!(status.is_err());
+        assert_eq!(
+            *status.err().unwrap(),
+            [BuildError::RenamedField {
+                field: "contact.jabber",
+                renamed_to: "contact.xmpp",
+                version: SpecVersion::V14,
+            }]
+        );
     }
 
     #[test]
@@ -674,12 +1372,272 @@ mod test {
         assert!(status.is_err());
     }
 
+    #[test]
+    fn test_builder_v14_reports_all_errors() {
+        let status = StatusBuilder::v14("foo")
+            .contact(Contact {
+                google: Some(GoogleContact::default()),
+                ..Contact::default()
+            })
+            .add_radio_show(RadioShow::default())
+            .add_issue_report_channel(IssueReportChannel::Email)
+            .build();
+        let fields: Vec<_> = status.err().unwrap().iter().map(BuildError::field).collect();
+        assert_eq!(
+            fields,
+            [
+                "logo",
+                "url",
+                "location",
+                "contact.google",
+                "radio_show",
+                "issue_report_channels"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_v13_fail_on_missing_state_and_channels() {
+        let status = StatusBuilder::v0_13("foo")
+            .logo("bar")
+            .url("foobar")
+            .location(Location::default())
+            .contact(Contact::default())
+            .spacefed(Spacefed::default())
+            .build();
+        assert_eq!(
+            *status.err().unwrap(),
+            [
+                BuildError::RequiredField {
+                    field: "spacefed.spacephone",
+                    version: SpecVersion::V0_13,
+                },
+                BuildError::EmptyList {
+                    field: "issue_report_channels",
+                    version: SpecVersion::V0_13,
+                },
+                BuildError::RequiredField {
+                    field: "state",
+                    version: SpecVersion::V0_13,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_v15() {
+        let status = StatusBuilder::v15("foo")
+            .logo("bar")
+            .url("foobar")
+            .state(State {
+                open: Some(true),
+                ..State::default()
+            })
+            .location(Location {
+                country_code: Some("CH".into()),
+                ..Location::default()
+            })
+            .contact(Contact {
+                signal: Some("+41000000000".into()),
+                ..Contact::default()
+            })
+            .add_linked_space(LinkedSpace {
+                endpoint: Some("https://example.org/spaceapi.json".into()),
+                ..LinkedSpace::default()
+            })
+            .build()
+            .unwrap();
+        assert_eq!(status.api, None);
+        assert_eq!(status.api_compatibility, Some(vec![ApiVersion::V15]));
+        assert_eq!(status.validate().violations, vec![]);
+    }
+
+    #[test]
+    fn test_builder_v14_fail_on_open_null() {
+        let status = StatusBuilder::v14("foo")
+            .logo("bar")
+            .url("foobar")
+            .state(State::default())
+            .location(Location::default())
+            .contact(Contact::default())
+            .build();
+        assert_eq!(
+            *status.err().unwrap(),
+            [BuildError::RequiredField {
+                field: "state.open",
+                version: SpecVersion::V14,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_builder_v15_fail_on_open_null() {
+        let status = StatusBuilder::v15("foo")
+            .logo("bar")
+            .url("foobar")
+            .state(State::default())
+            .location(Location::default())
+            .contact(Contact::default())
+            .build();
+        assert_eq!(
+            *status.err().unwrap(),
+            [BuildError::RequiredField {
+                field: "state.open",
+                version: SpecVersion::V14,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_builder_v14_fail_on_v15_fields() {
+        let status = StatusBuilder::v14("foo")
+            .logo("bar")
+            .url("foobar")
+            .location(Location {
+                hint: Some("Second floor".into()),
+                ..Location::default()
+            })
+            .contact(Contact {
+                telegram: Some("@foo".into()),
+                ..Contact::default()
+            })
+            .add_linked_space(LinkedSpace::default())
+            .build();
+        let fields: Vec<_> = status.err().unwrap().iter().map(BuildError::field).collect();
+        assert_eq!(fields, ["location.hint", "contact.telegram", "linked_spaces"]);
+    }
+
+    #[test]
+    fn test_builder_sensors_stream_cache() {
+        let status = StatusBuilder::v14("foo")
+            .logo("bar")
+            .url("foobar")
+            .location(Location::default())
+            .contact(Contact::default())
+            .add_sensor(TotalMemberCountSensor {
+                value: 42,
+                ..TotalMemberCountSensor::default()
+            })
+            .stream(Stream {
+                m4: Some("https://example.org/stream.m4".into()),
+                ..Stream::default()
+            })
+            .cache(Cache {
+                schedule: "m.05".into(),
+            })
+            .build()
+            .unwrap();
+        assert_eq!(status.sensors.unwrap().total_member_count[0].value, 42);
+        assert!(status.stream.is_some());
+        assert_eq!(status.cache.unwrap().schedule, "m.05");
+    }
+
+    #[test]
+    fn test_builder_v14_with_template_sensors() {
+        let builder = |builder: StatusBuilder, sensors: &Sensors| {
+            builder
+                .logo("bar")
+                .url("foobar")
+                .location(Location::default())
+                .contact(Contact::default())
+                .state(State {
+                    open: Some(false),
+                    ..State::default()
+                })
+                .sensors(sensors.clone())
+        };
+        let mut sensors = Sensors::default();
+        let temperature = TemperatureSensorTemplate {
+            metadata: SensorMetadataWithLocation {
+                location: "Hall".into(),
+                ..Default::default()
+            },
+            unit: TemperatureUnit::Celsius,
+        };
+        temperature.try_to_sensor("21.5", &mut sensors).unwrap();
+        assert!(sensors.temperature[0].metadata.lastchange.is_some());
+
+        for version in [StatusBuilder::new("foo"), StatusBuilder::mixed("foo")] {
+            let status = builder(version, &sensors)
+                .add_issue_report_channel(IssueReportChannel::Email)
+                .build()
+                .unwrap();
+            assert_eq!(status.sensors.unwrap().temperature[0].metadata.lastchange, None);
+        }
+
+        let carbondioxide = CarbonDioxideSensorTemplate {
+            metadata: temperature.metadata.clone(),
+            unit: CarbonDioxideUnit::PartsPerMillion,
+        };
+        carbondioxide.try_to_sensor("812", &mut sensors).unwrap();
+        let status = builder(StatusBuilder::v14("foo"), &sensors).build().unwrap();
+        let built = status.sensors.unwrap();
+        assert_eq!(built.temperature[0].metadata.lastchange, None);
+        assert_eq!(built.carbondioxide[0].metadata.lastchange, None);
+
+        let status = builder(StatusBuilder::v15("foo"), &sensors).build().unwrap();
+        assert_eq!(status.sensors, Some(sensors));
+    }
+
+    #[test]
+    fn test_builder_v14_fail_on_v15_sensor() {
+        let status = StatusBuilder::v14("foo")
+            .logo("bar")
+            .url("foobar")
+            .location(Location::default())
+            .contact(Contact::default())
+            .add_sensor(ParticulateMatterSensor::default())
+            .build();
+        assert_eq!(
+            *status.err().unwrap(),
+            [BuildError::UnavailableField {
+                field: "sensors.particulate_matter",
+                version: SpecVersion::V15,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_builder_v15_fail_on_stream_and_invalid_cache() {
+        let status = StatusBuilder::v15("foo")
+            .logo("bar")
+            .url("foobar")
+            .location(Location::default())
+            .contact(Contact::default())
+            .stream(Stream::default())
+            .cache(Cache {
+                schedule: "m.01".into(),
+            })
+            .build();
+        let errors = status.err().unwrap();
+        assert_eq!(
+            *errors,
+            [
+                BuildError::InvalidValue {
+                    field: "cache.schedule",
+                    value: "m.01".into(),
+                },
+                BuildError::RemovedField {
+                    field: "stream",
+                    version: SpecVersion::V15,
+                },
+            ]
+        );
+        assert_eq!(
+            errors.to_string(),
+            "cache.schedule has invalid value \"m.01\"; stream key was removed in v15"
+        );
+    }
+
     #[test]
     fn test_builder_mixed() {
         let status = StatusBuilder::mixed("foo")
             .logo("bar")
             .url("foobar")
-            .state(State::default())
+            .state(State {
+                open: Some(false),
+                ..State::default()
+            })
             .location(Location::default())
             .contact(Contact::default())
             .add_issue_report_channel(IssueReportChannel::Email)
@@ -694,7 +1652,7 @@ mod test {
                 logo: "bar".into(),
                 url: "foobar".into(),
                 state: Some(State {
-                    open: None,
+                    open: Some(false),
                     ..State::default()
                 }),
                 issue_report_channels: vec![IssueReportChannel::Email],
@@ -746,6 +1704,19 @@ mod test {
         assert_eq!(status.issue_report_channels, vec![IssueReportChannel::Email]);
     }
 
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema_respects_serde_attributes() {
+        let schema = serde_json::to_value(schemars::schema_for!(Status)).unwrap();
+        let feed = &schema["definitions"]["Feed"];
+        assert!(feed["properties"].get("type").is_some());
+        assert!(feed["properties"].get("type_").is_none());
+        assert_eq!(feed["required"], serde_json::json!(["url"]));
+        let required = schema["required"].as_array().unwrap();
+        assert!(!required.contains(&"issue_report_channels".into()));
+        assert!(required.contains(&"contact".into()));
+    }
+
     #[test]
     fn serialize_skip_none() {
         let f1 = Feed {
@@ -884,6 +1855,40 @@ mod test {
         assert!(serialized.contains("\"ext_ccc\":null"));
     }
 
+    /// Build a minimal status for every supported version combination,
+    /// serialize and deserialize it again and validate the result.
+    #[test]
+    fn serialize_deserialize_all_versions() {
+        let builders = [
+            (StatusBuilder::v0_13("foo"), vec![SpecVersion::V0_13]),
+            (StatusBuilder::v14("foo"), vec![SpecVersion::V14]),
+            (StatusBuilder::v15("foo"), vec![SpecVersion::V15]),
+            (
+                StatusBuilder::mixed("foo"),
+                vec![SpecVersion::V0_13, SpecVersion::V14],
+            ),
+        ];
+        for (builder, versions) in builders {
+            let mut builder = builder
+                .logo("bar")
+                .url("foobar")
+                .location(Location::default())
+                .contact(Contact::default())
+                .state(State {
+                    open: Some(true),
+                    ..State::default()
+                });
+            if versions.contains(&SpecVersion::V0_13) {
+                builder = builder.add_issue_report_channel(IssueReportChannel::Email);
+            }
+            let status = builder.build().unwrap();
+            let deserialized: Status = from_str(&to_string(&status).unwrap()).unwrap();
+            assert_eq!(status, deserialized);
+            assert_eq!(deserialized.declared_versions(), versions);
+            assert!(deserialized.validate().is_valid());
+        }
+    }
+
     #[test]
     fn deserialize_status() {
         let data = r#"{
@@ -913,6 +1918,56 @@ mod test {
         assert_eq!(keys.len(), 2)
     }
 
+    #[test]
+    fn state_transitions() {
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let mut status = Status::default();
+        let change = status.set_open_with(true, Some("Alice"), None, &at(100)).unwrap();
+        assert_eq!(
+            change,
+            StateChange {
+                previous: None,
+                previous_lastchange: None,
+                open: true,
+                lastchange: 100,
+                trigger_person: Some("Alice".into()),
+                message: None,
+            }
+        );
+
+        let state = status.state.as_mut().unwrap();
+        let before = state.clone();
+        assert_eq!(
+            state.transition_with(true, Some("Bob"), Some("Open until 22:00"), &at(200)),
+            None
+        );
+        assert_eq!(*state, before);
+
+        let change = state
+            .transition_with(false, Some("Bob"), Some("Closed for today"), &at(300))
+            .unwrap();
+        assert_eq!(change.previous, Some(true));
+        assert_eq!(change.previous_lastchange, Some(100));
+        assert_eq!(change.lastchange, 300);
+        assert_eq!(change.message.as_deref(), Some("Closed for today"));
+        assert_eq!(state.message.as_deref(), Some("Closed for today"));
+
+        state
+            .transition_with(true, Some("Alice"), None, &at(400))
+            .unwrap();
+        let change = state.transition_with(false, Some("Bob"), None, &at(500)).unwrap();
+        assert_eq!(change.previous_lastchange, Some(400));
+        assert_eq!(
+            *state,
+            State {
+                open: Some(false),
+                lastchange: Some(500),
+                trigger_person: Some("Bob".into()),
+                ..Default::default()
+            }
+        );
+    }
+
     mod serialize {
         use super::*;
 
@@ -940,6 +1995,9 @@ mod test {
         );
         test_serialize!(issue_report_channel_ml, IssueReportChannel::Ml, "\"ml\"");
 
+        test_serialize!(api_version_14, ApiVersion::V14, "\"14\"");
+        test_serialize!(api_version_15, ApiVersion::V15, "\"15\"");
+
         test_serialize!(stream_default, Stream::default(), "{}");
 
         test_serialize!(
@@ -984,6 +2042,8 @@ mod test {
             };
         }
 
+        test_deserialize!(api_version_15, "\"15\"", ApiVersion, ApiVersion::V15);
+
         test_deserialize!(
             issue_report_channel_email,
             "\"email\"",
@@ -0,0 +1,279 @@
+//! Validation of `Status` documents against the rules of their declared versions.
+
+use crate::status::{oldest, ApiVersion, BuildError, SpecVersion, Status};
+
+/// A single rule violation found when validating a `Status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// JSON pointer of the offending key, e.g. `/contact/jabber`
+    pub path: String,
+    /// The declared version whose rules are violated, `None` if the
+    /// declared versions themselves are invalid
+    pub version: Option<SpecVersion>,
+    pub error: BuildError,
+}
+
+impl Violation {
+    pub fn new(error: BuildError, version: Option<SpecVersion>) -> Self {
+        Violation {
+            path: error.json_pointer(),
+            version,
+            error,
+        }
+    }
+}
+
+/// The result of validating a `Status` with `Status::validate`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The versions declared through `api` and `api_compatibility`
+    pub versions: Vec<SpecVersion>,
+    /// Every violation found
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// Whether the document satisfies the rules of all declared versions.
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl Status {
+    /// Return the SpaceAPI versions this document declares to implement.
+    ///
+    /// An `api` value of `0.13` declares v0.13, every entry of
+    /// `api_compatibility` declares the respective version. Unsupported
+    /// `api` values are ignored.
+    pub fn declared_versions(&self) -> Vec<SpecVersion> {
+        let mut versions = vec![];
+        if self.api.as_deref() == Some("0.13") {
+            versions.push(SpecVersion::V0_13);
+        }
+        for version in self.api_compatibility.iter().flatten() {
+            let version = match version {
+                ApiVersion::V14 => SpecVersion::V14,
+                ApiVersion::V15 => SpecVersion::V15,
+            };
+            if !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        versions
+    }
+
+    /// Validate this document against the rules of every version it declares.
+    ///
+    /// These are the same rules `StatusBuilder::build` enforces, so a
+    /// deserialized document can be checked the same way as a built one.
+    /// The rules of each declared version are checked separately, so a
+    /// problem affecting several versions is reported once for each of them.
+    pub fn validate(&self) -> ValidationReport {
+        let versions = self.declared_versions();
+        let mut violations = vec![];
+
+        if let Some(api) = &self.api {
+            if api != "0.13" {
+                violations.push(Violation::new(
+                    BuildError::UnsupportedVersion {
+                        field: "api",
+                        value: api.clone(),
+                    },
+                    None,
+                ));
+            }
+        }
+        if versions.is_empty() {
+            violations.push(Violation::new(
+                BuildError::RequiredField {
+                    field: "api_compatibility",
+                    version: SpecVersion::V14,
+                },
+                None,
+            ));
+        }
+        for &version in &versions {
+            let mut errors = vec![];
+            self.verify_version(version, oldest(&versions), &mut errors);
+            violations.extend(
+                errors
+                    .into_iter()
+                    .map(|error| Violation::new(error, Some(version))),
+            );
+        }
+
+        ValidationReport { versions, violations }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::from_str;
+
+    fn parse(json: &str) -> Status {
+        from_str(json).unwrap()
+    }
+
+    #[test]
+    fn validate_v13() {
+        let status = parse(
+            r#"{"api":"0.13","space":"a","logo":"b","url":"c","location":{"lat":0.0,"lon":0.0},
+            "contact":{"jabber":"a@b"},"issue_report_channels":["email"],"state":{"open":true}}"#,
+        );
+        let report = status.validate();
+        assert_eq!(report.versions, vec![SpecVersion::V0_13]);
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn validate_v13_missing_state_and_channels() {
+        let status = parse(
+            r#"{"api":"0.13","space":"a","logo":"b","url":"c","location":{"lat":0.0,"lon":0.0,"timezone":"UTC"},
+            "contact":{}}"#,
+        );
+        let paths: Vec<_> = status
+            .validate()
+            .violations
+            .into_iter()
+            .map(|violation| violation.path)
+            .collect();
+        assert_eq!(paths, ["/issue_report_channels", "/state", "/location/timezone"]);
+    }
+
+    #[test]
+    fn validate_v14_removed_fields() {
+        let status = parse(
+            r#"{"api_compatibility":["14"],"space":"a","logo":"b","url":"c","location":{"lat":0.0,"lon":0.0},
+            "contact":{"jabber":"a@b"},"spacefed":{"spacenet":true,"spacesaml":false,"spacephone":false},
+            "radio_show":[{"name":"a","url":"b","type":"mp3","start":"c","end":"d"}]}"#,
+        );
+        let report = status.validate();
+        assert_eq!(report.versions, vec![SpecVersion::V14]);
+        assert_eq!(
+            report.violations,
+            vec![
+                Violation {
+                    path: "/spacefed/spacephone".into(),
+                    version: Some(SpecVersion::V14),
+                    error: BuildError::RemovedField {
+                        field: "spacefed.spacephone",
+                        version: SpecVersion::V14,
+                    },
+                },
+                Violation {
+                    path: "/contact/jabber".into(),
+                    version: Some(SpecVersion::V14),
+                    error: BuildError::RenamedField {
+                        field: "contact.jabber",
+                        renamed_to: "contact.xmpp",
+                        version: SpecVersion::V14,
+                    },
+                },
+                Violation {
+                    path: "/radio_show".into(),
+                    version: Some(SpecVersion::V14),
+                    error: BuildError::RemovedField {
+                        field: "radio_show",
+                        version: SpecVersion::V14,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_mixed() {
+        let status = parse(
+            r#"{"api":"0.13","api_compatibility":["14"],"space":"a","logo":"b","url":"c",
+            "location":{"lat":0.0,"lon":0.0},"contact":{},"issue_report_channels":["email"],
+            "state":{"open":null},"links":[{"name":"a","url":"b"}]}"#,
+        );
+        let report = status.validate();
+        assert_eq!(report.versions, vec![SpecVersion::V0_13, SpecVersion::V14]);
+        assert_eq!(
+            report.violations,
+            vec![
+                Violation::new(
+                    BuildError::UnavailableField {
+                        field: "links",
+                        version: SpecVersion::V14,
+                    },
+                    Some(SpecVersion::V0_13),
+                ),
+                Violation::new(
+                    BuildError::RequiredField {
+                        field: "state.open",
+                        version: SpecVersion::V14,
+                    },
+                    Some(SpecVersion::V14),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_each_declared_version() {
+        let status = parse(
+            r#"{"api_compatibility":["14","15"],"space":"a","logo":"b","url":"c",
+            "location":{"lat":0.0,"lon":0.0},"contact":{"telegram":"@a"},"state":{"open":null},
+            "stream":{"mp4":"d"},"sensors":{"door_locked":[{"location":"Door","value":true,"lastchange":1}]}}"#,
+        );
+        let report = status.validate();
+        assert_eq!(report.versions, vec![SpecVersion::V14, SpecVersion::V15]);
+        let open_required = BuildError::RequiredField {
+            field: "state.open",
+            version: SpecVersion::V14,
+        };
+        assert_eq!(
+            report.violations,
+            vec![
+                Violation::new(open_required.clone(), Some(SpecVersion::V14)),
+                Violation::new(
+                    BuildError::UnavailableField {
+                        field: "sensors.door_locked.lastchange",
+                        version: SpecVersion::V15,
+                    },
+                    Some(SpecVersion::V14),
+                ),
+                Violation::new(
+                    BuildError::UnavailableField {
+                        field: "contact.telegram",
+                        version: SpecVersion::V15,
+                    },
+                    Some(SpecVersion::V14),
+                ),
+                Violation::new(open_required, Some(SpecVersion::V15)),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_unsupported_version() {
+        let status = parse(
+            r#"{"api":"0.12","space":"a","logo":"b","url":"c","location":{"lat":0.0,"lon":0.0},"contact":{}}"#,
+        );
+        let report = status.validate();
+        assert!(report.versions.is_empty());
+        assert_eq!(
+            report.violations,
+            vec![
+                Violation::new(
+                    BuildError::UnsupportedVersion {
+                        field: "api",
+                        value: "0.12".into(),
+                    },
+                    None,
+                ),
+                Violation::new(
+                    BuildError::RequiredField {
+                        field: "api_compatibility",
+                        version: SpecVersion::V14,
+                    },
+                    None,
+                ),
+            ]
+        );
+    }
+}